@@ -82,5 +82,7 @@ int main() {
 
 	IntVector vec2;
 	vec2 = IntVector_new();
+	IntVector_free(&vec);
+	IntVector_free(&vec2);
 	return 0;
 }
\ No newline at end of file
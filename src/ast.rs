@@ -1,20 +1,114 @@
-use tree_sitter::{self, Language, Node, Parser, Tree};
-use tree_sitter_c;
+use std::sync::OnceLock;
+
+use tree_sitter::{self, Node, Parser, Point, Query, QueryCursor, Tree};
+
+use crate::{
+	data_struct::{EvaluableExpr, Identifier, Operator, Token},
+	debug,
+};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ProgramVariable {
 	pub name: String,
+	/// The base type, `IntVector` for `const struct IntVector* p[2]`
 	pub var_type: String,
+	pub pointer_depth: usize,
+	pub array_dimensions: usize,
+	/// `const`, `volatile`... of the type and of the pointers
+	pub qualifiers: Vec<String>,
+	/// `static`, `extern`...
+	pub storage_class: Option<String>,
 	pub implications: Vec<(usize, usize)>, // (start_byte, end_byte)
 	pub scope: (usize, usize),             // (start_byte, end_byte)
+	pub declaration: (usize, usize),       // (start_byte, end_byte)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assignment {
+	/// The assigned expression as written, e.g. `vec2`
+	pub left: String,
+	/// `=`, `+=`..., or `++` and `--` for `i++` and `--i`
+	pub operator: String,
+	/// None if what is assigned is not a variable, e.g. `s.vec`
+	pub target: Option<Place>,
+	pub value: (usize, usize), // (start_byte, end_byte)
+	pub start_byte: usize,
+	pub end_byte: usize,
+	pub start_position: Point,
+	pub end_position: Point,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionDefinition {
+	pub name: String,
+	pub start_byte: usize,
+	pub end_byte: usize,
+	pub body: (usize, usize), // (start_byte, end_byte)
+	/// In the order they are written, their scope is the whole function
+	pub parameters: Vec<ProgramVariable>,
+	/// `static`...
+	pub storage_class: Option<String>,
+}
+
+/// What was extracted from a piece of code
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SourceFacts {
+	pub variables: Vec<ProgramVariable>,
+	/// In evaluation order
+	pub calls: Vec<CallSite>,
+	/// In evaluation order
+	pub assignments: Vec<Assignment>,
+	pub functions: Vec<FunctionDefinition>,
+	/// The `return` statements, (start_byte, end_byte)
+	pub returns: Vec<(usize, usize)>,
+	pub null_checks: Vec<NullCheck>,
+	/// The functions declared or defined as never returning
+	pub noreturn: Vec<String>,
+}
+
+/// The variable an expression designates and how many times its address is taken,
+/// `&vec` is `vec` at 1, `*p` is `p` at -1 and `&arr[i]` is `arr` at 0
+#[derive(Debug, Clone, PartialEq)]
+pub struct Place {
+	pub variable: String,
+	pub indirection: isize,
+}
+
+/// A test of whether a variable is NULL, `g == NULL`, `!g` or `if (g)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct NullCheck {
+	pub place: Place,
+	pub start_byte: usize,
+	pub end_byte: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Argument {
+	pub text: String,
+	/// None if the argument is not a variable, e.g. `3` or `make()`
+	pub place: Option<Place>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallSite {
+	pub function: String,
+	pub arguments: Vec<Argument>,
+	pub start_byte: usize,
+	pub end_byte: usize,
+	pub start_position: Point,
+	pub end_position: Point,
 }
 
 pub fn parse_ast(file: &str) -> Tree {
 	let source_code = std::fs::read_to_string(file).unwrap();
-	let language = Language::from(tree_sitter_c::language());
+	return parse_source(&source_code);
+}
+
+pub fn parse_source(source_code: &str) -> Tree {
+	let language = tree_sitter_c::language();
 	let mut parser = Parser::new();
 	parser.set_language(&language).unwrap();
-	let tree = parser.parse(&source_code, None).unwrap();
+	let tree = parser.parse(source_code, None).unwrap();
 	return tree;
 }
 
@@ -30,14 +124,14 @@ pub fn print_tree(node: tree_sitter::Node, source_code: &str, depth: usize) {
 
 	let indent = "  ".repeat(depth);
 	if is_child {
-		println!(
+		debug!(
 			"{}{}: {}..{} ({}..{})",
 			indent, kind, start_byte, end_byte, start_position, end_position
 		);
 
 		if is_named {
 			let text = &source_code[start_byte..end_byte];
-			println!("{}  text: {:?}", indent, text);
+			debug!("{}  text: {:?}", indent, text);
 		}
 	}
 
@@ -47,143 +141,818 @@ pub fn print_tree(node: tree_sitter::Node, source_code: &str, depth: usize) {
 	}
 }
 
-pub fn parse_variables(tree: &Tree, source_code: &str) -> Vec<ProgramVariable> {
-	let mut variables = Vec::new();
-	let root_node = tree.root_node();
-	let mut stack = Vec::new();
-	stack.push(root_node);
-
-	while let Some(node) = stack.pop() {
-		let kind = node.kind();
-		if kind == "declaration" {
-			let mut variable = ProgramVariable {
-				name: "".to_string(),
-				var_type: "".to_string(),
-				implications: Vec::new(),
-				scope: (node.parent().unwrap().start_byte(), node.parent().unwrap().end_byte()),
-			};
-			let text = &source_code[node.start_byte()..node.end_byte()];
-
-			let mut found_init_declarator = false;
-			for i in 0..node.child_count() {
-				let child = node.child(i).unwrap();
-				let child_kind = child.kind();
-				if child_kind == "init_declarator" {
-					found_init_declarator = true;
-					println!("init_declarator : {:?}", child);
-					// find the call expression if it exists
-					let mut found_call_expression = false;
-					for j in 0..child.child_count() {
-						let grandchild = child.child(j).unwrap();
-						let grandchild_kind = grandchild.kind();
-						if grandchild_kind == "call_expression" {
-							found_call_expression = true;
-							let mut call_expression_start_byte = grandchild.start_byte();
-							// Type var = call_expression();
-							let expr = &source_code[child.start_byte()..grandchild.start_byte()];
-							let equal_sign = expr.rfind('=').unwrap();
-							println!("expr : {:?}", expr);
-							println!("text : {:?}", text);
-							let expr = &expr[..equal_sign].trim();
-							variable.name = expr.trim().to_string();
-							// find the type by looking at the name
-							let index_of_name = text.find(&variable.name).unwrap();
-							println!("index_of_name : {:?}", index_of_name);
-							let var_type = &source_code[node.start_byte()..node.start_byte() + index_of_name];
-							variable.var_type = var_type.trim().to_string();
-
-							variable.implications.push((call_expression_start_byte, grandchild.end_byte()));
-						}
-					}
-					if !found_call_expression {
-						let text = &source_code[node.start_byte()..node.end_byte()];
-						println!("text not found : {:?}", text);
-						let first_non_alphanumeric = text.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap();
-						let next_alphanumeric = text[first_non_alphanumeric..]
-							.find(|c: char| c.is_alphanumeric() || c == '_')
-							.unwrap();
-						println!("first_non_alphanumeric : {:?}", first_non_alphanumeric);
-						println!("next_alphanumeric : {:?}", next_alphanumeric);
-						println!("text non_alphanumeric : {:?}", &text[first_non_alphanumeric..]);
-						println!("text alphanumeric : {:?}", &text[..first_non_alphanumeric + next_alphanumeric]);
-						let var_type = &text[..first_non_alphanumeric + next_alphanumeric];
-						println!("var_type : {:?}", var_type);
-						variable.var_type = var_type.trim().to_string();
-						let equals_sign = text.find('=');
-						variable.name = text[first_non_alphanumeric + next_alphanumeric..equals_sign.unwrap()]
-							.trim()
-							.to_string();
-						variable.implications.push((node.start_byte(), node.end_byte()));
+fn text<'a>(node: Node, source_code: &'a str) -> &'a str {
+	return &source_code[node.start_byte()..node.end_byte()];
+}
+
+/// The name of a type, without the `struct`/`union`/`enum` keyword
+fn type_name(node: Node, source_code: &str) -> String {
+	match node.kind() {
+		"struct_specifier" | "union_specifier" | "enum_specifier" => match node.child_by_field_name("name") {
+			Some(name) => text(name, source_code).to_string(),
+			None => text(node, source_code).to_string(),
+		},
+		_ => text(node, source_code).split_whitespace().collect::<Vec<_>>().join(" "),
+	}
+}
+
+/// What a declarator declares: `**p[3] = x` is the name `p` with 2 pointers, 1 array dimension and `x` as initializer
+struct Declarator<'a> {
+	name: Node<'a>,
+	pointer_depth: usize,
+	array_dimensions: usize,
+	qualifiers: Vec<String>,
+	value: Option<Node<'a>>,
+}
+
+/// Unwraps the nested declarators of a declaration, gives None for function declarations
+fn unwrap_declarator<'a>(node: Node<'a>, source_code: &str) -> Option<Declarator<'a>> {
+	let mut declarator = Declarator {
+		name: node,
+		pointer_depth: 0,
+		array_dimensions: 0,
+		qualifiers: Vec::new(),
+		value: None,
+	};
+	let mut node = node;
+	loop {
+		match node.kind() {
+			"identifier" => {
+				declarator.name = node;
+				return Some(declarator);
+			}
+			"init_declarator" => {
+				declarator.value = node.child_by_field_name("value");
+				node = node.child_by_field_name("declarator")?;
+			}
+			"pointer_declarator" => {
+				declarator.pointer_depth += 1;
+				for i in 0..node.named_child_count() {
+					let child = node.named_child(i).unwrap();
+					if child.kind() == "type_qualifier" {
+						declarator.qualifiers.push(text(child, source_code).to_string());
 					}
 				}
+				node = node.child_by_field_name("declarator")?;
+			}
+			"array_declarator" => {
+				declarator.array_dimensions += 1;
+				node = node.child_by_field_name("declarator")?;
 			}
-			if !found_init_declarator {
-				let text = &source_code[node.start_byte()..node.end_byte()];
-				println!("declaration : {:?}", text);
-				let last_space = text.rfind(|c: char| !c.is_alphanumeric() && c.is_whitespace()).unwrap();
-				variable.name = text[last_space..text.len() - 1].trim().to_string();
-				let var_type = &source_code[node.start_byte()..node.start_byte() + last_space];
-				variable.var_type = var_type.trim().to_string();
+			"parenthesized_declarator" | "attributed_declarator" => {
+				node = node.named_child(0)?;
+			}
+			// `(*callback)(int)` is a pointer to a function, `f(int)` is a function
+			"function_declarator" => {
+				node = node.child_by_field_name("declarator")?;
+				if node.kind() != "parenthesized_declarator" {
+					return None;
+				}
 			}
-			variables.push(variable);
+			_ => return None,
 		}
+	}
+}
+
+/// The variables declared by a `declaration` node, with their type taken apart,
+/// `static const IntVector* a = &v, b[2];` gives `a` and `b`
+fn declaration_variables(node: Node, source_code: &str) -> Vec<ProgramVariable> {
+	let mut variables = Vec::new();
+	let Some(type_node) = node.child_by_field_name("type")
+	else {
+		return variables;
+	};
+	let var_type = type_name(type_node, source_code);
+	let mut qualifiers = Vec::new();
+	let mut storage_class = None;
+	for i in 0..node.named_child_count() {
+		let child = node.named_child(i).unwrap();
+		match child.kind() {
+			"type_qualifier" => qualifiers.push(text(child, source_code).to_string()),
+			"storage_class_specifier" => storage_class = Some(text(child, source_code).to_string()),
+			_ => {}
+		}
+	}
+	let parent = node.parent().unwrap_or(node);
+	let mut cursor = node.walk();
+	for declarator_node in node.children_by_field_name("declarator", &mut cursor) {
+		let Some(declarator) = unwrap_declarator(declarator_node, source_code)
 		else {
-			for i in 0..node.child_count() {
-				stack.push(node.child(i).unwrap());
+			continue;
+		};
+		let mut variable_qualifiers = qualifiers.clone();
+		variable_qualifiers.extend(declarator.qualifiers);
+		let implications = match declarator.value {
+			Some(value) => vec![(value.start_byte(), value.end_byte())],
+			None => Vec::new(),
+		};
+		let variable = ProgramVariable {
+			name: text(declarator.name, source_code).to_string(),
+			var_type: var_type.clone(),
+			pointer_depth: declarator.pointer_depth,
+			array_dimensions: declarator.array_dimensions,
+			qualifiers: variable_qualifiers,
+			storage_class: storage_class.clone(),
+			implications,
+			scope: (parent.start_byte(), parent.end_byte()),
+			declaration: (node.start_byte(), node.end_byte()),
+		};
+		debug!("variable: {:?}", variable);
+		variables.push(variable);
+	}
+	return variables;
+}
+
+/// What variable an expression designates, through `&`, `*`, `[]` and parentheses
+pub fn place(node: Node, source_code: &str) -> Option<Place> {
+	match node.kind() {
+		"identifier" => {
+			return Some(Place {
+				variable: text(node, source_code).to_string(),
+				indirection: 0,
+			});
+		}
+		"parenthesized_expression" => return place(node.named_child(0)?, source_code),
+		"pointer_expression" => {
+			let mut inner = place(node.child_by_field_name("argument")?, source_code)?;
+			match text(node.child_by_field_name("operator")?, source_code) {
+				"&" => inner.indirection += 1,
+				_ => inner.indirection -= 1,
 			}
+			return Some(inner);
 		}
+		"subscript_expression" => {
+			let mut inner = place(node.child_by_field_name("argument")?, source_code)?;
+			inner.indirection -= 1;
+			return Some(inner);
+		}
+		_ => return None,
 	}
-	return variables;
 }
 
-pub fn print_variables(variable: &ProgramVariable, source_code: &str) {
-	println!("{}: {} {:?}", variable.name, variable.var_type, variable.scope);
-	for (start_byte, end_byte) in &variable.implications {
-		let text = &source_code[*start_byte..*end_byte];
-		println!("  Implication: {}", text);
-	}
-}
-
-pub fn entry_point_c<'a>(tree: &'a tree_sitter::Tree, source_code: &str) -> tree_sitter::Node<'a> {
-	// find main()
-	let mut main = None;
-	let mut stack = vec![tree.root_node()];
-	while let Some(node) = stack.pop() {
-		if node.kind() == "function_definition" {
-			// println!("FOUND FUNCTION DEFINITION");
-			// print_tree(node, source_code, 0);
-			// print all the children
-			// for i in 0..node.child_count() {
-			//     let child = node.child(i).unwrap();
-			//     println!("child {}: {}, {}", i, child.kind(), child.kind_id());
-			// }
-			let mut good_child = None;
-			for i in 0..node.child_count() {
-				let child = node.child(i).unwrap();
-				if child.kind() == "function_declarator" {
-					good_child = Some(child);
-					break;
+/// A C expression made of variables, numbers, `true` and `false` as the guards write them, in reverse polish notation.
+/// None if it has anything else, e.g. a call or a `?:`
+pub fn expression(node: Node, source_code: &str) -> Option<EvaluableExpr> {
+	let mut tokens = Vec::new();
+	match node.kind() {
+		"identifier" => tokens.push(Token::Operand(Identifier::var(text(node, source_code)))),
+		"number_literal" => tokens.push(Token::Operand(Identifier::const_num(text(node, source_code).parse().ok()?))),
+		"true" => tokens.push(Token::Operand(Identifier::const_bool(true))),
+		"false" => tokens.push(Token::Operand(Identifier::const_bool(false))),
+		"parenthesized_expression" if node.named_child_count() == 1 => return expression(node.named_child(0)?, source_code),
+		"unary_expression" => {
+			tokens.extend(expression(node.child_by_field_name("argument")?, source_code)?);
+			match text(node.child_by_field_name("operator")?, source_code) {
+				"!" => tokens.push(Token::Operation(Operator::Not)),
+				"-" => tokens.push(Token::Operation(Operator::UnaryMinus)),
+				"+" => {}
+				_ => return None,
+			}
+		}
+		"binary_expression" => {
+			tokens.extend(expression(node.child_by_field_name("left")?, source_code)?);
+			tokens.extend(expression(node.child_by_field_name("right")?, source_code)?);
+			let operator = text(node.child_by_field_name("operator")?, source_code);
+			tokens.push(Token::Operation(Operator::from_string(operator).ok()?));
+		}
+		_ => return None,
+	}
+	return Some(tokens);
+}
+
+/// The test the value returned by the call spanning `call` passes when the condition `node` holds,
+/// e.g. `(NotEquals, 0)` for `f() != 0`, `f()` or `!(f() == 0)`. None if the condition is not a comparison of it to a constant
+pub fn returned_value_test(node: Node, source_code: &str, call: (usize, usize)) -> Option<(Operator, i32)> {
+	let is_call = |mut node: Node| {
+		while node.kind() == "parenthesized_expression" && node.named_child_count() == 1 {
+			node = node.named_child(0).unwrap();
+		}
+		return (node.start_byte(), node.end_byte()) == call;
+	};
+	let constant = |node: Node| match node.kind() {
+		"null" | "false" => Some(0),
+		"true" => Some(1),
+		"number_literal" => text(node, source_code).parse().ok(),
+		_ => None,
+	};
+	if is_call(node) {
+		return Some((Operator::NotEquals, 0));
+	}
+	match node.kind() {
+		"parenthesized_expression" if node.named_child_count() == 1 => return returned_value_test(node.named_child(0)?, source_code, call),
+		"unary_expression" if text(node.child_by_field_name("operator")?, source_code) == "!" => {
+			let (op, value) = returned_value_test(node.child_by_field_name("argument")?, source_code, call)?;
+			return Some((op.negation(), value));
+		}
+		"binary_expression" => {
+			let op = Operator::from_string(text(node.child_by_field_name("operator")?, source_code)).ok()?;
+			if op != Operator::Equals && !op.is_comparison() {
+				return None;
+			}
+			let (left, right) = (node.child_by_field_name("left")?, node.child_by_field_name("right")?);
+			if is_call(left) {
+				return Some((op, constant(right)?));
+			}
+			if is_call(right) {
+				return Some((op.mirror(), constant(left)?));
+			}
+			return None;
+		}
+		_ => return None,
+	}
+}
+
+/// The expression spanning `range`, see [`expression`]
+pub fn expression_at(node: Node, source_code: &str, (start_byte, end_byte): (usize, usize)) -> Option<EvaluableExpr> {
+	return expression(node.descendant_for_byte_range(start_byte, end_byte)?, source_code);
+}
+
+/// What variable the expression spanning `range` designates, see [`place`]
+pub fn place_at(node: Node, source_code: &str, (start_byte, end_byte): (usize, usize)) -> Option<Place> {
+	return place(node.descendant_for_byte_range(start_byte, end_byte)?, source_code);
+}
+
+fn call_site(node: Node, function: Node, argument_list: Node, source_code: &str) -> CallSite {
+	let mut arguments = Vec::new();
+	for i in 0..argument_list.named_child_count() {
+		let argument = argument_list.named_child(i).unwrap();
+		if argument.kind() != "comment" {
+			arguments.push(Argument {
+				text: text(argument, source_code).to_string(),
+				place: place(argument, source_code),
+			});
+		}
+	}
+	return CallSite {
+		function: text(function, source_code).to_string(),
+		arguments,
+		start_byte: node.start_byte(),
+		end_byte: node.end_byte(),
+		start_position: node.start_position(),
+		end_position: node.end_position(),
+	};
+}
+
+/// The function declarator inside the declarator of a function definition, `*make(int)` gives `make(int)`
+fn function_declarator(declarator: Node) -> Option<Node> {
+	let mut node = declarator;
+	loop {
+		match node.kind() {
+			"function_declarator" => return Some(node),
+			"pointer_declarator" => node = node.child_by_field_name("declarator")?,
+			"parenthesized_declarator" | "attributed_declarator" => node = node.named_child(0)?,
+			_ => return None,
+		}
+	}
+}
+
+/// The name of the function declared by the declarator of a function definition,
+/// `*make(int)` gives `make`
+fn function_name<'a>(declarator: Node, source_code: &'a str) -> Option<&'a str> {
+	let name = function_declarator(declarator)?.child_by_field_name("declarator")?;
+	return match name.kind() {
+		"identifier" => Some(text(name, source_code)),
+		_ => None,
+	};
+}
+
+/// A parameter declared without a name, `IntVector*` in `void f(int, IntVector*)`, None for the `void` of `f(void)`
+fn unnamed_parameter(parameter: Node, source_code: &str) -> Option<ProgramVariable> {
+	let var_type = type_name(parameter.child_by_field_name("type")?, source_code);
+	let mut declarator = parameter.child_by_field_name("declarator");
+	if var_type == "void" && declarator.is_none() {
+		return None;
+	}
+	let mut qualifiers = Vec::new();
+	let mut cursor = parameter.walk();
+	for child in parameter.named_children(&mut cursor) {
+		if child.kind() == "type_qualifier" {
+			qualifiers.push(text(child, source_code).to_string());
+		}
+	}
+	let (mut pointer_depth, mut array_dimensions) = (0, 0);
+	while let Some(node) = declarator {
+		match node.kind() {
+			"abstract_pointer_declarator" => pointer_depth += 1,
+			"abstract_array_declarator" => array_dimensions += 1,
+			_ => {}
+		}
+		declarator = match node.kind() {
+			"abstract_parenthesized_declarator" => node.named_child(0),
+			_ => node.child_by_field_name("declarator"),
+		};
+	}
+	return Some(ProgramVariable {
+		name: String::new(),
+		var_type,
+		pointer_depth,
+		array_dimensions,
+		qualifiers,
+		storage_class: None,
+		implications: Vec::new(),
+		scope: (parameter.start_byte(), parameter.end_byte()),
+		declaration: (parameter.start_byte(), parameter.end_byte()),
+	});
+}
+
+/// The parameters of the function defined by `node`, one for each position, with an empty name if it has none.
+/// `void f(void)` has none
+fn parameters(node: Node, source_code: &str) -> Vec<ProgramVariable> {
+	let Some(list) = node
+		.child_by_field_name("declarator")
+		.and_then(function_declarator)
+		.and_then(|declarator| declarator.child_by_field_name("parameters"))
+	else {
+		return Vec::new();
+	};
+	let mut parameters = Vec::new();
+	let mut cursor = list.walk();
+	for parameter in list
+		.named_children(&mut cursor)
+		.filter(|child| child.kind() == "parameter_declaration")
+	{
+		let mut variables = declaration_variables(parameter, source_code);
+		if variables.is_empty() {
+			variables.extend(unnamed_parameter(parameter, source_code));
+		}
+		for mut variable in variables {
+			variable.scope = (node.start_byte(), node.end_byte());
+			parameters.push(variable);
+		}
+	}
+	return parameters;
+}
+
+/// The name of the function a declaration or a definition declares, if it is marked as never returning,
+/// with `_Noreturn`, `noreturn`, `__attribute__((noreturn))` or `[[noreturn]]`
+fn noreturn_function<'a>(node: Node, source_code: &'a str) -> Option<&'a str> {
+	let declarator = node.child_by_field_name("declarator")?;
+	let marked = |node: Node| {
+		let mut cursor = node.walk();
+		let mut children = node.children(&mut cursor);
+		return children.any(|child| {
+			return matches!(child.kind(), "type_qualifier" | "attribute_specifier" | "attribute_declaration")
+				&& text(child, source_code).to_lowercase().contains("noreturn");
+		});
+	};
+	if !marked(node) && !marked(declarator) {
+		return None;
+	}
+	return function_name(declarator, source_code);
+}
+
+/// Declarations, calls, assignments and function definitions
+const EXTRACTION_QUERY: &str = "
+(declaration) @declaration
+(call_expression function: (_) @function arguments: (argument_list) @arguments) @call
+(assignment_expression left: (_) @left operator: _ @operator right: (_) @right) @assignment
+(update_expression argument: (_) @left operator: _ @operator) @assignment
+(update_expression operator: _ @operator argument: (_) @left) @assignment
+(function_definition declarator: (_) @declarator body: (compound_statement) @body) @function_definition
+(return_statement) @return
+(binary_expression left: (_) @checked operator: [\"==\" \"!=\"] right: [(null) (number_literal)] @null) @null_check
+(binary_expression left: [(null) (number_literal)] @null operator: [\"==\" \"!=\"] right: (_) @checked) @null_check
+(unary_expression operator: \"!\" argument: (_) @checked) @null_check
+(if_statement condition: (parenthesized_expression (identifier) @checked) @null_check)
+(while_statement condition: (parenthesized_expression (identifier) @checked) @null_check)
+";
+
+fn extraction_query() -> &'static Query {
+	static QUERY: OnceLock<Query> = OnceLock::new();
+	return QUERY.get_or_init(|| Query::new(&tree_sitter_c::language(), EXTRACTION_QUERY).unwrap());
+}
+
+/// Everything TinyGuard needs to know about the code under `node`, gathered in a single pass
+pub fn extract(node: Node, source_code: &str) -> SourceFacts {
+	let query = extraction_query();
+	let mut facts = SourceFacts::default();
+	let mut cursor = QueryCursor::new();
+	for query_match in cursor.matches(query, node, source_code.as_bytes()) {
+		let capture = |name: &str| {
+			let index = query.capture_index_for_name(name).unwrap();
+			return query_match
+				.captures
+				.iter()
+				.find(|capture| capture.index == index)
+				.map(|capture| capture.node);
+		};
+		// the capture naming a pattern is the one spanning the whole match
+		let root = query_match
+			.captures
+			.iter()
+			.max_by_key(|capture| capture.node.end_byte() - capture.node.start_byte())
+			.unwrap();
+		match query.capture_names()[root.index as usize] {
+			"declaration" => {
+				let node = capture("declaration").unwrap();
+				facts.variables.extend(declaration_variables(node, source_code));
+				facts.noreturn.extend(noreturn_function(node, source_code).map(str::to_string));
+			}
+			"call" => {
+				let call = call_site(
+					capture("call").unwrap(),
+					capture("function").unwrap(),
+					capture("arguments").unwrap(),
+					source_code,
+				);
+				facts.calls.push(call);
+			}
+			"assignment" => {
+				let node = capture("assignment").unwrap();
+				let left = capture("left").unwrap();
+				// `i++` has no value, it is the whole expression
+				let right = capture("right").unwrap_or(node);
+				facts.assignments.push(Assignment {
+					left: text(left, source_code).to_string(),
+					target: place(left, source_code),
+					operator: text(capture("operator").unwrap(), source_code).to_string(),
+					value: (right.start_byte(), right.end_byte()),
+					start_byte: node.start_byte(),
+					end_byte: node.end_byte(),
+					start_position: node.start_position(),
+					end_position: node.end_position(),
+				});
+			}
+			"null_check" => {
+				let null = capture("null").map(|null| text(null, source_code));
+				if null.is_some_and(|null| null != "NULL" && null != "nullptr" && null != "0") {
+					continue;
 				}
+				let node = capture("null_check").unwrap();
+				if let Some(place) = place(capture("checked").unwrap(), source_code) {
+					facts.null_checks.push(NullCheck {
+						place,
+						start_byte: node.start_byte(),
+						end_byte: node.end_byte(),
+					});
+				}
+			}
+			"return" => {
+				let node = capture("return").unwrap();
+				facts.returns.push((node.start_byte(), node.end_byte()));
 			}
-			let child = good_child.unwrap();
-			let text = &source_code[child.start_byte()..child.end_byte()];
-			// remove everything after the first '('
-			let text = text.split('(').next().unwrap();
-			// remove the first word
-			let text = text.split_whitespace().last().unwrap();
-			// println!("function name: {}", text);
-			if text == "main" {
-				main = Some(node);
-				break;
+			"function_definition" => {
+				let node = capture("function_definition").unwrap();
+				let Some(name) = function_name(capture("declarator").unwrap(), source_code)
+				else {
+					continue;
+				};
+				facts.noreturn.extend(noreturn_function(node, source_code).map(str::to_string));
+				let body = capture("body").unwrap();
+				facts.functions.push(FunctionDefinition {
+					name: name.to_string(),
+					start_byte: node.start_byte(),
+					end_byte: node.end_byte(),
+					body: (body.start_byte(), body.end_byte()),
+					parameters: parameters(node, source_code),
+					storage_class: (0..node.named_child_count())
+						.map(|i| node.named_child(i).unwrap())
+						.find(|child| child.kind() == "storage_class_specifier")
+						.map(|child| text(child, source_code).to_string()),
+				});
 			}
+			_ => {}
 		}
+	}
+	facts.variables.sort_by_key(|variable| variable.declaration.0);
+	// the arguments of a call are evaluated before the call itself
+	facts.calls.sort_by_key(|call| (call.end_byte, call.start_byte));
+	facts
+		.assignments
+		.sort_by_key(|assignment| (assignment.end_byte, assignment.start_byte));
+	return facts;
+}
+
+/// Every variable declared in the source
+pub fn parse_variables(tree: &Tree, source_code: &str) -> Vec<ProgramVariable> {
+	return extract(tree.root_node(), source_code).variables;
+}
+
+pub fn print_variables(variable: &ProgramVariable, source_code: &str) {
+	debug!("{}: {} {:?}", variable.name, variable.var_type, variable.scope);
+	for (start_byte, end_byte) in &variable.implications {
+		let text = &source_code[*start_byte..*end_byte];
+		debug!("  Implication: {}", text);
+	}
+}
 
-		let child_count = node.child_count();
-		for i in 0..child_count {
-			let child = node.child(i).unwrap();
-			stack.push(child);
+/// Whether `name` matches a pattern where `*` stands for any characters and `?` for any character
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+	let pattern = pattern.chars().collect::<Vec<_>>();
+	let name = name.chars().collect::<Vec<_>>();
+	// the prefixes of the name the prefix of the pattern read so far can match
+	let mut matching = vec![true];
+	matching.extend(vec![false; name.len()]);
+	for p in pattern {
+		let mut next = vec![false; name.len() + 1];
+		for i in 0..=name.len() {
+			next[i] = match p {
+				'*' => matching[i] || (i > 0 && next[i - 1]),
+				'?' => i > 0 && matching[i - 1],
+				c => i > 0 && matching[i - 1] && name[i - 1] == c,
+			};
 		}
+		matching = next;
+	}
+	return matching[name.len()];
+}
+
+/// The definitions of the functions whose name matches one of `patterns`, see [`matches_pattern`],
+/// `facts` being the ones [`extract`] found in `tree`
+pub fn entry_points_c<'a>(tree: &'a tree_sitter::Tree, facts: &SourceFacts, patterns: &[String]) -> Vec<tree_sitter::Node<'a>> {
+	return facts
+		.functions
+		.iter()
+		.filter(|function| patterns.iter().any(|pattern| matches_pattern(pattern, &function.name)))
+		.map(|function| {
+			return tree
+				.root_node()
+				.descendant_for_byte_range(function.start_byte, function.end_byte)
+				.unwrap();
+		})
+		.collect();
+}
+
+/// The definition of `main`, None in a library or a file of tests
+pub fn entry_point_c<'a>(tree: &'a tree_sitter::Tree, facts: &SourceFacts) -> Option<tree_sitter::Node<'a>> {
+	return entry_points_c(tree, facts, &["main".to_string()]).into_iter().next();
+}
+
+/// All the function calls made inside `node`, in the order they are evaluated
+/// (the arguments of a call are evaluated before the call itself), out of the ones of `facts`
+pub fn call_sites(facts: &SourceFacts, node: Node) -> Vec<CallSite> {
+	return facts
+		.calls
+		.iter()
+		.filter(|call| node.start_byte() <= call.start_byte && call.end_byte <= node.end_byte())
+		.cloned()
+		.collect();
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn variables(source_code: &str) -> Vec<ProgramVariable> {
+		let tree = parse_source(source_code);
+		return parse_variables(&tree, source_code);
+	}
+
+	#[test]
+	fn test_initialised_declaration() {
+		let source_code = "int main() { IntVector vec = IntVector_new(); }";
+		let variables = variables(source_code);
+		assert_eq!(variables.len(), 1);
+		assert_eq!(variables[0].name, "vec");
+		assert_eq!(variables[0].var_type, "IntVector");
+		let (start, end) = variables[0].implications[0];
+		assert_eq!(&source_code[start..end], "IntVector_new()");
+	}
+
+	#[test]
+	fn test_pointer_declaration() {
+		let variables = variables("int main() { IntVector* p = &vec; }");
+		assert_eq!(variables[0].name, "p");
+		assert_eq!(variables[0].var_type, "IntVector");
+		assert_eq!(variables[0].pointer_depth, 1);
+	}
+
+	#[test]
+	fn test_several_declarators() {
+		let variables = variables("int main() { IntVector a, *b, c[4][2]; }");
+		let names = variables
+			.iter()
+			.map(|v| (v.name.as_str(), v.pointer_depth, v.array_dimensions))
+			.collect::<Vec<_>>();
+		assert_eq!(names, vec![("a", 0, 0), ("b", 1, 0), ("c", 0, 2)]);
+		assert!(variables.iter().all(|v| v.var_type == "IntVector" && v.implications.is_empty()));
+	}
+
+	#[test]
+	fn test_qualifiers_and_storage_class() {
+		let variables = variables("static const struct IntVector v = { 0 };\nextern IntVector* const w;");
+		assert_eq!(variables[0].name, "v");
+		assert_eq!(variables[0].var_type, "IntVector");
+		assert_eq!(variables[0].qualifiers, vec!["const".to_string()]);
+		assert_eq!(variables[0].storage_class, Some("static".to_string()));
+		assert_eq!(variables[1].name, "w");
+		assert_eq!(variables[1].qualifiers, vec!["const".to_string()]);
+		assert_eq!(variables[1].storage_class, Some("extern".to_string()));
+	}
+
+	#[test]
+	fn test_function_declarations_are_not_variables() {
+		let variables = variables("IntVector IntVector_new(void);\nint (*callback)(int);");
+		assert_eq!(variables.len(), 1);
+		assert_eq!(variables[0].name, "callback");
+		assert_eq!(variables[0].pointer_depth, 1);
+	}
+
+	#[test]
+	fn test_extract() {
+		let source_code = "int* make(int n) { return 0; }
+int main() {
+	IntVector vec2;
+	vec2 = IntVector_new();
+	IntVector_push_back(&vec2, make(1));
+}";
+		let tree = parse_source(source_code);
+		let facts = extract(tree.root_node(), source_code);
+		let functions = facts.functions.iter().map(|f| f.name.as_str()).collect::<Vec<_>>();
+		assert_eq!(functions, vec!["make", "main"]);
+		assert_eq!(facts.returns.len(), 1);
+		let calls = facts.calls.iter().map(|c| c.function.as_str()).collect::<Vec<_>>();
+		assert_eq!(calls, vec!["IntVector_new", "make", "IntVector_push_back"]);
+		let arguments = facts.calls[2].arguments.iter().map(|a| a.text.as_str()).collect::<Vec<_>>();
+		assert_eq!(arguments, vec!["&vec2", "make(1)"]);
+		let vec2 = Place {
+			variable: "vec2".to_string(),
+			indirection: 1,
+		};
+		assert_eq!(facts.calls[2].arguments[0].place, Some(vec2));
+		assert_eq!(facts.calls[2].arguments[1].place, None);
+		assert_eq!(facts.assignments.len(), 1);
+		assert_eq!(facts.assignments[0].left, "vec2");
+		assert_eq!(facts.assignments[0].operator, "=");
+		let (start, end) = facts.assignments[0].value;
+		assert_eq!(&source_code[start..end], "IntVector_new()");
+		assert_eq!(facts.variables.iter().map(|v| v.name.as_str()).collect::<Vec<_>>(), vec!["vec2"]);
 	}
 
-	return main.unwrap();
+	#[test]
+	fn test_parameters() {
+		let source_code = "static IntVector* pick(const IntVector** all, int n, ...) { return 0; }\nint main(void) {}";
+		let tree = parse_source(source_code);
+		let facts = extract(tree.root_node(), source_code);
+		let parameters = &facts.functions[0].parameters;
+		assert_eq!(parameters.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(), vec!["all", "n"]);
+		assert_eq!((parameters[0].var_type.as_str(), parameters[0].pointer_depth), ("IntVector", 2));
+		assert_eq!(parameters[0].qualifiers, vec!["const"]);
+		assert_eq!(parameters[1].scope, (0, facts.functions[0].end_byte));
+		assert!(facts.functions[1].parameters.is_empty());
+		assert_eq!(facts.functions[0].storage_class.as_deref(), Some("static"));
+		assert_eq!(facts.functions[1].storage_class, None);
+		assert!(facts.variables.is_empty());
+		let source_code = "void f(int, const IntVector* [2], IntVector* v) {}";
+		let tree = parse_source(source_code);
+		let parameters = &extract(tree.root_node(), source_code).functions[0].parameters;
+		assert_eq!(parameters.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(), vec!["", "", "v"]);
+		assert_eq!(
+			(
+				parameters[1].var_type.as_str(),
+				parameters[1].pointer_depth,
+				parameters[1].array_dimensions
+			),
+			("IntVector", 1, 1)
+		);
+		assert_eq!(parameters[1].qualifiers, vec!["const"]);
+	}
+
+	#[test]
+	fn test_entry_point() {
+		let source_code = "void f() {}\nint main(void) { f(); }";
+		let tree = parse_source(source_code);
+		let facts = extract(tree.root_node(), source_code);
+		let main = entry_point_c(&tree, &facts).unwrap();
+		assert_eq!(main.kind(), "function_definition");
+		assert_eq!(main.start_byte(), 12);
+		assert_eq!(
+			call_sites(&facts, main)
+				.iter()
+				.map(|call| call.function.as_str())
+				.collect::<Vec<_>>(),
+			vec!["f"]
+		);
+		let source_code = "void test_push() {}\nvoid test_sort() {}\nvoid helper() {}";
+		let tree = parse_source(source_code);
+		let facts = extract(tree.root_node(), source_code);
+		assert!(entry_point_c(&tree, &facts).is_none());
+		let tests = entry_points_c(&tree, &facts, &["test_*".to_string()]);
+		assert_eq!(tests.iter().map(|test| test.start_byte()).collect::<Vec<_>>(), vec![0, 20]);
+	}
+
+	#[test]
+	fn test_matches_pattern() {
+		assert!(matches_pattern("main", "main"));
+		assert!(!matches_pattern("main", "main2"));
+		assert!(matches_pattern("test_*", "test_"));
+		assert!(matches_pattern("test_*", "test_push_back"));
+		assert!(!matches_pattern("test_*", "my_test_push"));
+		assert!(matches_pattern("*_test", "sort_test"));
+		assert!(matches_pattern("t?st_*_?", "test_a_b_c"));
+		assert!(!matches_pattern("t?st", "tst"));
+		assert!(matches_pattern("*", "anything"));
+	}
+
+	#[test]
+	fn test_places() {
+		let source_code = "int main() { f(&vec, p, *pp, &arr[i], (&arr[0][1]), 3); }";
+		let tree = parse_source(source_code);
+		let facts = extract(tree.root_node(), source_code);
+		let places = facts.calls[0]
+			.arguments
+			.iter()
+			.map(|argument| argument.place.as_ref().map(|place| (place.variable.as_str(), place.indirection)))
+			.collect::<Vec<_>>();
+		assert_eq!(
+			places,
+			vec![
+				Some(("vec", 1)),
+				Some(("p", 0)),
+				Some(("pp", -1)),
+				Some(("arr", 0)),
+				Some(("arr", -1)),
+				None
+			]
+		);
+	}
+
+	#[test]
+	fn test_null_checks() {
+		let source_code = "int main() { if (g == NULL) {} if (!h) {} if (i) {} while (0 != j) {} if (k == 1) {} }";
+		let tree = parse_source(source_code);
+		let facts = extract(tree.root_node(), source_code);
+		let checked = facts
+			.null_checks
+			.iter()
+			.map(|check| check.place.variable.as_str())
+			.collect::<Vec<_>>();
+		assert_eq!(checked, vec!["g", "h", "i", "j"]);
+	}
+
+	#[test]
+	fn test_noreturn_functions() {
+		let source_code = "_Noreturn void die(int code);
+void fail(const char* m) __attribute__((noreturn));
+__attribute__((noreturn)) static void stop(void) { for (;;); }
+[[noreturn]] void quit(void);
+noreturn void bye(void);
+void run(void);
+int main() { return 0; }";
+		let tree = parse_source(source_code);
+		let facts = extract(tree.root_node(), source_code);
+		assert_eq!(facts.noreturn, vec!["die", "fail", "stop", "quit", "bye"]);
+	}
+
+	#[test]
+	fn test_returned_value_tests() {
+		let conditions = [
+			"f(&g) != 0",
+			"!f(&g)",
+			"(f(&g))",
+			"0 > f(&g)",
+			"!(NULL == (f(&g)))",
+			"f(&g) + 1",
+			"f(&g) == n",
+		];
+		let tests = conditions.map(|condition| {
+			let source_code = format!("int main() {{ if ({}) {{}} }}", condition);
+			let tree = parse_source(&source_code);
+			let call = &extract(tree.root_node(), &source_code).calls[0];
+			let start = source_code.find(condition).unwrap();
+			let node = tree.root_node().descendant_for_byte_range(start, start + condition.len()).unwrap();
+			return returned_value_test(node, &source_code, (call.start_byte, call.end_byte));
+		});
+		use Operator::*;
+		assert_eq!(
+			tests,
+			[
+				Some((NotEquals, 0)),
+				Some((Equals, 0)),
+				Some((NotEquals, 0)),
+				Some((Lower, 0)),
+				Some((NotEquals, 0)),
+				None,
+				None
+			]
+		);
+	}
+
+	#[test]
+	fn test_expressions() {
+		let source_code = "int main() { if (!(done || n >= -m * 2)) {} if (f(n)) {} if (n ? 1 : 2) {} i++; --j; k += 2; }";
+		let tree = parse_source(source_code);
+		let facts = extract(tree.root_node(), source_code);
+		let conditions = ["(!(done || n >= -m * 2))", "(f(n))", "(n ? 1 : 2)"].map(|condition| {
+			let start = source_code.find(condition).unwrap();
+			return expression_at(tree.root_node(), source_code, (start, start + condition.len()));
+		});
+		use Operator::*;
+		let (var, num) = (
+			|name| Token::Operand(Identifier::var(name)),
+			|n| Token::Operand(Identifier::const_num(n)),
+		);
+		let operations = |ops: &[Operator]| ops.iter().map(|&op| Token::Operation(op)).collect::<Vec<_>>();
+		let mut expected = vec![var("done"), var("n"), var("m")];
+		expected.extend(operations(&[UnaryMinus]));
+		expected.push(num(2));
+		expected.extend(operations(&[Multiplication, GreaterOrEqual, Or, Not]));
+		assert_eq!(conditions, [Some(expected), None, None]);
+		let updates = facts
+			.assignments
+			.iter()
+			.map(|assignment| (assignment.left.as_str(), assignment.operator.as_str()))
+			.collect::<Vec<_>>();
+		assert_eq!(updates, vec![("i", "++"), ("j", "--"), ("k", "+=")]);
+	}
 }
@@ -0,0 +1,477 @@
+//! Control-flow graphs of C function bodies, built from their tree-sitter nodes
+
+use tree_sitter::Node;
+
+use crate::debug;
+
+/// A sequence of statements always run one after the other
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Block {
+	/// (start_byte, end_byte) of each statement, in the order they run.
+	/// The conditions of branches and loops are statements of their own,
+	/// and the closing bracket of a compound statement is one so that the end of a scope can be found in the graph
+	pub statements: Vec<(usize, usize)>,
+	pub successors: Vec<usize>,
+	/// Set when the block ends with the condition of an `if` or a loop
+	pub branch: Option<Branch>,
+}
+
+/// Where a condition leads, both are successors of its block
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Branch {
+	/// (start_byte, end_byte) of the condition, the last statement of its block
+	pub condition: (usize, usize),
+	/// Reached when it holds
+	pub taken: usize,
+	pub not_taken: usize,
+}
+
+/// The control-flow graph of a function body, the blocks are in the order they are created
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cfg {
+	pub blocks: Vec<Block>,
+	pub entry: usize,
+	/// Reached by every `return` and by the end of the body
+	pub exit: usize,
+}
+
+impl Cfg {
+	/// The block and the index of the statement spanning `range`, if it is inside the function
+	pub fn statement_at(&self, (start, end): (usize, usize)) -> Option<(usize, usize)> {
+		for (block, statements) in self.blocks.iter().map(|block| &block.statements).enumerate() {
+			if let Some(statement) = statements.iter().position(|&(s, e)| s <= start && end <= e) {
+				return Some((block, statement));
+			}
+		}
+		return None;
+	}
+
+	/// The blocks a loop goes back to, the targets of the edges leading to a block still being explored from the entry
+	pub fn loop_heads(&self) -> Vec<bool> {
+		fn visit(cfg: &Cfg, block: usize, visited: &mut [bool], exploring: &mut [bool], heads: &mut [bool]) {
+			visited[block] = true;
+			exploring[block] = true;
+			for &successor in &cfg.blocks[block].successors {
+				if exploring[successor] {
+					heads[successor] = true;
+				}
+				else if !visited[successor] {
+					visit(cfg, successor, visited, exploring, heads);
+				}
+			}
+			exploring[block] = false;
+		}
+		let mut heads = vec![false; self.blocks.len()];
+		let mut visited = vec![false; self.blocks.len()];
+		let mut exploring = vec![false; self.blocks.len()];
+		visit(self, self.entry, &mut visited, &mut exploring, &mut heads);
+		return heads;
+	}
+
+	/// The blocks reached from the entry
+	pub fn reachable(&self) -> Vec<bool> {
+		return self.reached_from(self.entry);
+	}
+
+	/// The blocks reached from `block`, itself included
+	pub fn reached_from(&self, block: usize) -> Vec<bool> {
+		let mut reached = vec![false; self.blocks.len()];
+		let mut stack = vec![block];
+		while let Some(block) = stack.pop() {
+			if reached[block] {
+				continue;
+			}
+			reached[block] = true;
+			stack.extend(&self.blocks[block].successors);
+		}
+		return reached;
+	}
+}
+
+struct Builder<'a> {
+	source_code: &'a str,
+	blocks: Vec<Block>,
+	/// The block the next statement goes in
+	current: usize,
+	exit: usize,
+	/// Where `break` and `continue` go, for the innermost loop or switch
+	breaks: Vec<usize>,
+	continues: Vec<usize>,
+	labels: Vec<(String, usize)>,
+	/// The blocks ending with a `goto`, and their label
+	gotos: Vec<(usize, String)>,
+	/// The functions that never return, a call to one ends the path
+	noreturn: &'a [String],
+}
+
+impl Builder<'_> {
+	fn new_block(&mut self) -> usize {
+		self.blocks.push(Block::default());
+		return self.blocks.len() - 1;
+	}
+
+	fn edge(&mut self, from: usize, to: usize) {
+		if !self.blocks[from].successors.contains(&to) {
+			self.blocks[from].successors.push(to);
+		}
+	}
+
+	fn push(&mut self, node: Node) {
+		let current = self.current;
+		self.blocks[current].statements.push((node.start_byte(), node.end_byte()));
+	}
+
+	/// Continues in `block`, reached from the current one
+	fn enter(&mut self, block: usize) {
+		self.edge(self.current, block);
+		self.current = block;
+	}
+
+	/// Ends the current block with a jump to `target`, what follows is unreachable until something jumps to it
+	fn jump(&mut self, target: Option<usize>) {
+		if let Some(target) = target {
+			self.edge(self.current, target);
+		}
+		self.current = self.new_block();
+	}
+
+	/// Whether `node` is a statement calling a function that never returns, like `exit(1);`
+	fn never_returns(&self, node: Node) -> bool {
+		let Some(call) = node.named_child(0).filter(|call| call.kind() == "call_expression")
+		else {
+			return false;
+		};
+		let function = call.child_by_field_name("function").unwrap();
+		let name = function.utf8_text(self.source_code.as_bytes()).unwrap();
+		return self.noreturn.iter().any(|noreturn| noreturn == name);
+	}
+
+	fn statement(&mut self, node: Node) {
+		match node.kind() {
+			"compound_statement" => {
+				let mut cursor = node.walk();
+				for child in node.named_children(&mut cursor) {
+					self.statement(child);
+				}
+				let end = node.end_byte();
+				let current = self.current;
+				self.blocks[current].statements.push((end - 1, end));
+			}
+			"if_statement" => {
+				self.push(node.child_by_field_name("condition").unwrap());
+				let condition = self.current;
+				let after = self.new_block();
+				let consequence = self.new_block();
+				self.current = consequence;
+				self.edge(condition, consequence);
+				self.statement(node.child_by_field_name("consequence").unwrap());
+				self.edge(self.current, after);
+				let mut not_taken = after;
+				match node.child_by_field_name("alternative") {
+					Some(alternative) => {
+						self.current = self.new_block();
+						not_taken = self.current;
+						self.edge(condition, self.current);
+						// the `else` keyword followed by a statement
+						let mut cursor = alternative.walk();
+						let statement = alternative.named_children(&mut cursor).last();
+						if let Some(statement) = statement {
+							self.statement(statement);
+						}
+						self.edge(self.current, after);
+					}
+					None => self.edge(condition, after),
+				}
+				self.branch(condition, consequence, not_taken);
+				self.current = after;
+			}
+			"while_statement" => {
+				let head = self.new_block();
+				let after = self.new_block();
+				self.enter(head);
+				self.push(node.child_by_field_name("condition").unwrap());
+				self.edge(head, after);
+				let body = self.loop_body(node, head, head, after);
+				self.branch(head, body, after);
+			}
+			"do_statement" => {
+				let condition = self.new_block();
+				let after = self.new_block();
+				let body = self.loop_body(node, condition, condition, after);
+				self.current = condition;
+				self.push(node.child_by_field_name("condition").unwrap());
+				self.edge(condition, body);
+				self.edge(condition, after);
+				self.branch(condition, body, after);
+				self.current = after;
+			}
+			"for_statement" => {
+				if let Some(initializer) = node.child_by_field_name("initializer") {
+					self.push(initializer);
+				}
+				let head = self.new_block();
+				let update = self.new_block();
+				let after = self.new_block();
+				self.enter(head);
+				// `for (;;)` is only left through a `break`
+				let condition = node.child_by_field_name("condition");
+				if let Some(condition) = condition {
+					self.push(condition);
+					self.edge(head, after);
+				}
+				if let Some(expression) = node.child_by_field_name("update") {
+					self.blocks[update]
+						.statements
+						.push((expression.start_byte(), expression.end_byte()));
+				}
+				self.edge(update, head);
+				let body = self.loop_body(node, update, update, after);
+				if condition.is_some() {
+					self.branch(head, body, after);
+				}
+			}
+			"switch_statement" => self.switch(node),
+			"break_statement" => {
+				let target = self.breaks.last().copied();
+				self.push(node);
+				self.jump(target);
+			}
+			"continue_statement" => {
+				let target = self.continues.last().copied();
+				self.push(node);
+				self.jump(target);
+			}
+			"return_statement" => {
+				self.push(node);
+				self.jump(Some(self.exit));
+			}
+			"goto_statement" => {
+				self.push(node);
+				let label = node.child_by_field_name("label").unwrap();
+				let label = label.utf8_text(self.source_code.as_bytes()).unwrap().to_string();
+				self.gotos.push((self.current, label));
+				self.jump(None);
+			}
+			"labeled_statement" => {
+				let block = self.new_block();
+				self.enter(block);
+				let label = node.child_by_field_name("label").unwrap();
+				let label = label.utf8_text(self.source_code.as_bytes()).unwrap().to_string();
+				self.labels.push((label, block));
+				let mut cursor = node.walk();
+				let statement = node.named_children(&mut cursor).last();
+				if let Some(statement) = statement.filter(|statement| statement.kind() != "statement_identifier") {
+					self.statement(statement);
+				}
+			}
+			"expression_statement" if self.never_returns(node) => {
+				self.push(node);
+				self.jump(None);
+			}
+			"comment" => {}
+			_ => self.push(node),
+		}
+	}
+
+	/// Marks `block`, ending with a condition, as going to `taken` when it holds and to `not_taken` otherwise
+	fn branch(&mut self, block: usize, taken: usize, not_taken: usize) {
+		let condition = *self.blocks[block].statements.last().unwrap();
+		self.blocks[block].branch = Some(Branch {
+			condition,
+			taken,
+			not_taken,
+		});
+	}
+
+	/// Builds the body of a loop in a new block, `continue` goes to `next` and the end of the body to `back`
+	fn loop_body(&mut self, node: Node, next: usize, back: usize, after: usize) -> usize {
+		let head = self.current;
+		let body = self.new_block();
+		self.edge(head, body);
+		self.current = body;
+		self.breaks.push(after);
+		self.continues.push(next);
+		self.statement(node.child_by_field_name("body").unwrap());
+		self.breaks.pop();
+		self.continues.pop();
+		self.edge(self.current, back);
+		self.current = after;
+		return body;
+	}
+
+	/// Each `case` starts a block reached from the condition and from the case before it, which falls through
+	fn switch(&mut self, node: Node) {
+		self.push(node.child_by_field_name("condition").unwrap());
+		let condition = self.current;
+		let after = self.new_block();
+		self.breaks.push(after);
+		self.current = self.new_block();
+		let mut has_default = false;
+		let body = node.child_by_field_name("body").unwrap();
+		let mut cursor = body.walk();
+		for child in body.named_children(&mut cursor) {
+			if child.kind() != "case_statement" {
+				self.statement(child);
+				continue;
+			}
+			let case = self.new_block();
+			self.enter(case);
+			self.edge(condition, case);
+			let value = child.child_by_field_name("value");
+			has_default |= value.is_none();
+			let mut case_cursor = child.walk();
+			for statement in child.named_children(&mut case_cursor) {
+				if Some(statement) != value {
+					self.statement(statement);
+				}
+			}
+		}
+		let end = body.end_byte();
+		let current = self.current;
+		self.blocks[current].statements.push((end - 1, end));
+		self.breaks.pop();
+		self.edge(self.current, after);
+		if !has_default {
+			self.edge(condition, after);
+		}
+		self.current = after;
+	}
+}
+
+/// The control-flow graph of `body`, the compound statement of a function definition.
+/// The paths calling one of the `noreturn` functions end there
+pub fn build(body: Node, source_code: &str, noreturn: &[String]) -> Cfg {
+	let mut builder = Builder {
+		source_code,
+		blocks: Vec::new(),
+		current: 0,
+		exit: 0,
+		breaks: Vec::new(),
+		continues: Vec::new(),
+		labels: Vec::new(),
+		gotos: Vec::new(),
+		noreturn,
+	};
+	let entry = builder.new_block();
+	builder.exit = builder.new_block();
+	builder.current = entry;
+	builder.statement(body);
+	let (current, exit) = (builder.current, builder.exit);
+	builder.edge(current, exit);
+	for (block, label) in std::mem::take(&mut builder.gotos) {
+		match builder.labels.iter().find(|(name, _)| *name == label) {
+			Some(&(_, target)) => builder.edge(block, target),
+			None => debug!("`goto {}` has no label", label),
+		}
+	}
+	return Cfg {
+		blocks: builder.blocks,
+		entry,
+		exit: builder.exit,
+	};
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::ast::parse_source;
+
+	/// The graph of the body of the only function of `source_code`, with the statements as text
+	fn graph(source_code: &str) -> (Cfg, Vec<Vec<String>>) {
+		let tree = parse_source(source_code);
+		let function = tree.root_node().named_child(0).unwrap();
+		let noreturn = ["exit".to_string(), "die".to_string()];
+		let cfg = build(function.child_by_field_name("body").unwrap(), source_code, &noreturn);
+		let texts = cfg
+			.blocks
+			.iter()
+			.map(|block| block.statements.iter().map(|&(s, e)| source_code[s..e].to_string()).collect())
+			.collect();
+		return (cfg, texts);
+	}
+
+	/// The index of the block holding the statement `text`
+	fn block_of(texts: &[Vec<String>], text: &str) -> usize {
+		return texts
+			.iter()
+			.position(|block| block.iter().any(|statement| statement == text))
+			.unwrap();
+	}
+
+	#[test]
+	fn test_if_else() {
+		let (cfg, texts) = graph("int main() { a(); if (x) { b(); } else c(); d(); }");
+		let condition = block_of(&texts, "(x)");
+		assert_eq!(condition, cfg.entry);
+		assert_eq!(texts[condition], vec!["a();", "(x)"]);
+		let then = block_of(&texts, "b();");
+		let otherwise = block_of(&texts, "c();");
+		let after = block_of(&texts, "d();");
+		assert_eq!(cfg.blocks[condition].successors, vec![then, otherwise]);
+		assert_eq!(cfg.blocks[then].successors, vec![after]);
+		assert_eq!(cfg.blocks[otherwise].successors, vec![after]);
+		assert_eq!(cfg.blocks[after].successors, vec![cfg.exit]);
+		let branch = cfg.blocks[condition].branch.unwrap();
+		assert_eq!(branch.condition, *cfg.blocks[condition].statements.last().unwrap());
+		assert_eq!((branch.taken, branch.not_taken), (then, otherwise));
+		assert_eq!(cfg.blocks[then].branch, None);
+	}
+
+	#[test]
+	fn test_loops() {
+		let (cfg, texts) =
+			graph("int main() { while (x) { if (y) break; a(); continue; b(); } for (i = 0; i < 3; i++) c(); do d(); while (z); }");
+		let head = block_of(&texts, "(x)");
+		let a = block_of(&texts, "a();");
+		assert_eq!(cfg.blocks[a].successors, vec![head]);
+		let for_head = block_of(&texts, "i < 3");
+		// the `break` leaves the loop, `b();` is unreachable
+		let brk = block_of(&texts, "break;");
+		assert_eq!(texts[cfg.blocks[brk].successors[0]], vec!["i = 0"]);
+		assert!(!cfg.reachable()[block_of(&texts, "b();")]);
+		let update = block_of(&texts, "i++");
+		assert_eq!(cfg.blocks[update].successors, vec![for_head]);
+		assert_eq!(cfg.blocks[block_of(&texts, "c();")].successors, vec![update]);
+		let d = block_of(&texts, "d();");
+		let condition = block_of(&texts, "(z)");
+		assert_eq!(cfg.blocks[d].successors, vec![condition]);
+		assert!(cfg.blocks[condition].successors.contains(&d));
+		assert_eq!(cfg.blocks[condition].branch.map(|branch| branch.taken), Some(d));
+		assert_eq!(cfg.blocks[head].branch.map(|branch| branch.taken), Some(block_of(&texts, "(y)")));
+		let after_for = cfg.blocks[for_head].branch.unwrap().not_taken;
+		assert!(cfg.reached_from(after_for)[d] && !cfg.reached_from(d)[for_head]);
+		let heads = (0..cfg.blocks.len()).filter(|&block| cfg.loop_heads()[block]).collect::<Vec<_>>();
+		assert_eq!(heads, vec![head, for_head, d]);
+	}
+
+	#[test]
+	fn test_switch_goto_return() {
+		let (cfg, texts) =
+			graph("int main() { switch (x) { case 1: a(); case 2: b(); break; default: c(); } if (y) goto end; d(); return 1; end: e(); }");
+		let condition = block_of(&texts, "(x)");
+		let (a, b, c) = (block_of(&texts, "a();"), block_of(&texts, "b();"), block_of(&texts, "c();"));
+		assert_eq!(cfg.blocks[condition].successors, vec![a, b, c]);
+		// `case 1` falls through
+		assert!(cfg.blocks[a].successors.contains(&b));
+		let d = block_of(&texts, "d();");
+		assert_eq!(cfg.blocks[d].successors, vec![cfg.exit]);
+		let goto = block_of(&texts, "goto end;");
+		let e = block_of(&texts, "e();");
+		assert_eq!(cfg.blocks[goto].successors, vec![e]);
+		let start = texts[0][0].len();
+		assert_eq!(cfg.statement_at((start, start)), None);
+	}
+
+	#[test]
+	fn test_noreturn() {
+		let (cfg, texts) = graph("int main() { if (x) { a(); exit(1); b(); } else die(); c(); }");
+		let exit = block_of(&texts, "exit(1);");
+		assert!(cfg.blocks[exit].successors.is_empty());
+		assert!(cfg.blocks[block_of(&texts, "die();")].successors.is_empty());
+		let reachable = cfg.reachable();
+		assert!(!reachable[block_of(&texts, "b();")]);
+		// the `if` has no way out, what follows it is unreachable
+		assert!(!reachable[block_of(&texts, "c();")]);
+		assert!(!reachable[cfg.exit]);
+	}
+}
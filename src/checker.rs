@@ -0,0 +1,2650 @@
+use crate::{
+	ast::{self, Assignment, CallSite, FunctionDefinition, NullCheck, ProgramVariable, SourceFacts},
+	cfg::{self, Cfg},
+	data_struct::{
+		expression_variables, AbstractEnvironment, AbstractValue, Assignement, Call, Constant, DataStruct, Escape, EvaluableExpr,
+		HandleKind, Identifier, Operator, Token, Truth, Warning,
+	},
+	debug,
+	diagnostic::{Diagnostic, Location, RelatedLocation, Report, Rule, Severity, SourceFile, StateChange, Timeline},
+	summary, verbose,
+};
+use std::{
+	collections::VecDeque,
+	ops::{Index, IndexMut},
+};
+use tree_sitter::Tree;
+
+/// What the checker looks for on top of the rules of the guards
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+	/// Calls and destructors on destroyed objects, for the DataStructs with destructors
+	pub use_after_destroy: bool,
+	/// Objects constructed but never destroyed, for the DataStructs with destructors
+	pub leaks: bool,
+	/// The severity of the rules broken on some of the paths reaching a call only, if lower than the one of the rule.
+	/// None to only report the rules broken on every path
+	pub may_severity: Option<Severity>,
+	/// Follow the paths of the entry points one by one instead of joining them where they meet,
+	/// so that the branches testing the same local variables agree with each other
+	pub path_sensitive: bool,
+	/// The most paths followed one by one, past it they are joined
+	pub path_budget: usize,
+	/// The functions that never return on top of the standard ones and of the ones marked `_Noreturn`,
+	/// the paths calling them end there without leaking anything
+	pub noreturn: Vec<String>,
+	/// The functions checked on their own, `*` and `?` match any characters and any character,
+	/// e.g. `test_*`. Their DataStruct parameters hold objects of their caller nothing is known of
+	pub entry_points: Vec<String>,
+	/// What the functions no guard or summary describes do to the objects they are given,
+	/// for the DataStructs whose handle does not tell
+	pub escape: Escape,
+}
+
+impl Default for Options {
+	fn default() -> Options {
+		Options {
+			use_after_destroy: true,
+			leaks: true,
+			may_severity: Some(Severity::Warning),
+			path_sensitive: false,
+			path_budget: 1000,
+			noreturn: Vec::new(),
+			entry_points: vec!["main".to_string()],
+			escape: Escape::Havoc,
+		}
+	}
+}
+
+/// What the functions no guard or summary describes do to the objects of `data_struct`
+pub fn escape_policy(data_struct: &DataStruct, options: &Options) -> Escape {
+	return data_struct.handle.escapes.unwrap_or(options.escape);
+}
+
+/// The functions of the C library that never return
+const NORETURN_FUNCTIONS: [&str; 8] = [
+	"exit",
+	"_Exit",
+	"quick_exit",
+	"abort",
+	"longjmp",
+	"siglongjmp",
+	"__builtin_trap",
+	"__builtin_unreachable",
+];
+
+/// The functions that end the paths calling them, the standard ones, the ones of the options and the ones marked in the source
+pub fn noreturn_functions(options: &Options, facts: &SourceFacts) -> Vec<String> {
+	return NORETURN_FUNCTIONS
+		.iter()
+		.map(|function| function.to_string())
+		.chain(options.noreturn.iter().cloned())
+		.chain(facts.noreturn.iter().cloned())
+		.collect();
+}
+
+pub fn location(file: &str, call: &CallSite) -> Location {
+	Location {
+		file: file.to_string(),
+		line: call.start_position.row + 1,
+		column: call.start_position.column + 1,
+		end_line: call.end_position.row + 1,
+		end_column: call.end_position.column + 1,
+		start_byte: call.start_byte,
+		end_byte: call.end_byte,
+	}
+}
+
+fn timeline(file: &str, source_code: &str, variable: &ProgramVariable, environment: &AbstractEnvironment) -> Timeline {
+	return Timeline {
+		variable: variable.name.clone(),
+		data_struct: variable.var_type.clone(),
+		declaration: Location::from_bytes(file, source_code, variable.declaration.0, variable.declaration.1),
+		initial: environment.to_assocs(),
+		changes: Vec::new(),
+	};
+}
+
+/// Where a tracked object is in its life, a DataStruct without constructors is always alive
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Lifecycle {
+	/// Declared, but no constructor ran on it yet
+	Unconstructed,
+	Alive,
+	/// Given to a destructor
+	Destroyed,
+}
+
+/// The meta data of an abstract object, shared by the variables holding it and the pointers to it
+#[derive(Debug, Clone, PartialEq)]
+struct State {
+	environment: AbstractEnvironment,
+	/// For each meta data, the call that gave it its current value
+	provenance: Vec<(String, RelatedLocation)>,
+	/// Every lifecycle the object can be in on the paths reaching here, never empty
+	lifecycle: Vec<Lifecycle>,
+	/// Where the object got in its current lifecycle
+	lifecycle_provenance: Option<RelatedLocation>,
+	/// Constructed by the code being checked, which then has to destroy it
+	owned: bool,
+	/// Stands for several objects, e.g. all the elements of an array
+	summary: bool,
+	/// Returned by a constructor of a nullable handle and not compared to NULL since
+	may_be_null: bool,
+}
+
+impl State {
+	fn new(environment: AbstractEnvironment) -> State {
+		State {
+			environment,
+			provenance: Vec::new(),
+			lifecycle: vec![Lifecycle::Alive],
+			lifecycle_provenance: None,
+			owned: false,
+			summary: false,
+			may_be_null: false,
+		}
+	}
+
+	/// What is known of the object when it can come from either `self` or `other`
+	fn join(&self, other: &State) -> State {
+		let mut state = self.clone();
+		state.environment = self.environment.join(&other.environment);
+		// a meta data set at different places on each path has been set at all of them
+		for (name, related) in &other.provenance {
+			let same_place = state
+				.provenance
+				.iter_mut()
+				.find(|(kept_name, kept)| kept_name == name && kept.location == related.location);
+			match same_place {
+				// set to a new value at the next iteration of a loop
+				Some((_, kept)) => *kept = related.clone(),
+				None => state.provenance.push((name.clone(), related.clone())),
+			}
+		}
+		for &lifecycle in &other.lifecycle {
+			if !state.may_be(lifecycle) {
+				state.lifecycle.push(lifecycle);
+			}
+		}
+		if state.lifecycle_provenance.is_none() {
+			state.lifecycle_provenance = other.lifecycle_provenance.clone();
+		}
+		state.owned |= other.owned;
+		state.summary |= other.summary;
+		state.may_be_null |= other.may_be_null;
+		return state;
+	}
+
+	/// Joins the states at the start of a loop, returns the meta data made unknown to stop following the loop
+	fn widen(&self, other: &State) -> (State, Vec<String>) {
+		let mut state = self.join(other);
+		let (environment, widened) = self.environment.widen(&other.environment);
+		state.environment = environment;
+		return (state, widened);
+	}
+
+	fn may_be(&self, lifecycle: Lifecycle) -> bool {
+		return self.lifecycle.contains(&lifecycle);
+	}
+
+	/// On every path reaching here
+	fn must_be(&self, lifecycle: Lifecycle) -> bool {
+		return self.lifecycle == [lifecycle];
+	}
+
+	fn related(&self, names: &[String]) -> Vec<RelatedLocation> {
+		return self
+			.provenance
+			.iter()
+			.filter(|(name, _)| names.contains(name))
+			.map(|(_, related)| related.clone())
+			.collect();
+	}
+}
+
+/// What a tracked variable, or the memory a pointer points to, holds
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value {
+	/// An abstract object, a shallow copy holds the same one as the original
+	Object(usize),
+	/// The address of a cell
+	Pointer(usize),
+	/// A handle or a pointer set to `NULL`
+	Null,
+	/// e.g. an uninitialised pointer
+	Unknown,
+}
+
+fn join_values(value: &Value, other: &Value) -> Value {
+	if value == other {
+		return *value;
+	}
+	return Value::Unknown;
+}
+
+/// Where an object or a cell is created, the same place creates the same one on every path and at every iteration
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Site {
+	Declaration(usize),
+	/// What a parameter of the entry point points to, through `depth` pointers
+	Parameter {
+		variable: usize,
+		depth: usize,
+	},
+	Store {
+		variable: usize,
+		statement: usize,
+	},
+}
+
+/// Cells or objects by index, None for the ones not created on the paths reaching here
+#[derive(Debug, Clone, PartialEq)]
+struct Slots<T>(Vec<Option<T>>);
+
+impl<T: Clone> Slots<T> {
+	fn set(&mut self, index: usize, value: T) {
+		if self.0.len() <= index {
+			self.0.resize(index + 1, None);
+		}
+		self.0[index] = Some(value);
+	}
+
+	fn join(&self, other: &Slots<T>, mut join: impl FnMut(&T, &T) -> T) -> Slots<T> {
+		let mut joined = Slots(Vec::new());
+		for index in 0..self.0.len().max(other.0.len()) {
+			let value = match (self.0.get(index).cloned().flatten(), other.0.get(index)) {
+				(Some(value), Some(Some(other))) => Some(join(&value, other)),
+				(value, other) => value.or(other.cloned().flatten()),
+			};
+			joined.0.push(value);
+		}
+		return joined;
+	}
+}
+
+impl<T> Index<usize> for Slots<T> {
+	type Output = T;
+
+	fn index(&self, index: usize) -> &T {
+		return self.0[index].as_ref().unwrap();
+	}
+}
+
+impl<T> IndexMut<usize> for Slots<T> {
+	fn index_mut(&mut self, index: usize) -> &mut T {
+		return self.0[index].as_mut().unwrap();
+	}
+}
+
+fn in_scope(variable: &ProgramVariable, byte: usize) -> bool {
+	return variable.declaration.0 <= byte && byte <= variable.scope.1;
+}
+
+/// Finds the variable named `name` visible at `byte`, the innermost one if it is shadowed
+fn visible_variable(tracked: &[ProgramVariable], name: &str, data_struct: &str, byte: usize) -> Option<usize> {
+	let mut found: Option<usize> = None;
+	for (i, variable) in tracked.iter().enumerate() {
+		if variable.name != name || variable.var_type != data_struct || !in_scope(variable, byte) {
+			continue;
+		}
+		match found {
+			Some(j) if tracked[j].scope.0 >= variable.scope.0 => {}
+			_ => found = Some(i),
+		}
+	}
+	return found;
+}
+
+/// The tracked variable a call is made on, found by matching its arguments against the `$self` pattern of the call.
+/// With `&$self`, `f(&vec)` binds `vec`, `f(p)` the vector `p` points to and `f(&arr[i])` the elements of `arr`
+pub fn bind_self(tracked: &[ProgramVariable], call: &Call, call_site: &CallSite, data_struct: &str) -> Option<usize> {
+	let (position, indirection) = call.self_pattern()?;
+	let place = call_site.arguments.get(position)?.place.as_ref()?;
+	let index = visible_variable(tracked, &place.variable, data_struct, call_site.start_byte)?;
+	let variable = &tracked[index];
+	// $self is the DataStruct itself, what is left once every pointer and array of the variable is gone through
+	let depth = (variable.pointer_depth + variable.array_dimensions) as isize;
+	if depth + place.indirection != indirection {
+		debug!("`{}` does not match `{}`", call_site.arguments[position].text, call.signature());
+		return None;
+	}
+	return Some(index);
+}
+
+fn rule_id(data_struct: &DataStruct, call: &Call, kind: &str, index: usize) -> String {
+	return format!("{}.{}.{}{}", data_struct.name, call.function_name(), kind, index);
+}
+
+fn rule(data_struct: &DataStruct, call: &Call, warning: &Warning, kind: &str, index: usize, severity: Severity) -> Rule {
+	let message = match &warning.message {
+		Some(message) => message.clone(),
+		None => format!("`{}` breaks a rule of {}", call.signature(), data_struct.name),
+	};
+	return Rule {
+		id: rule_id(data_struct, call, kind, index),
+		data_struct: data_struct.name.clone(),
+		call: call.signature().to_string(),
+		severity,
+		message,
+		hint: warning.hint.clone(),
+		docs: warning.docs.clone(),
+	};
+}
+
+/// The `Denies` and `Warn` entries of a call, with their kind and severity
+pub fn call_rules(call: &Call) -> Vec<(&[Warning], &'static str, Severity)> {
+	return vec![
+		(call.deny.as_deref().unwrap_or_default(), "deny", Severity::Error),
+		(call.warn.as_deref().unwrap_or_default(), "warn", Severity::Warning),
+	];
+}
+
+/// Whether the text of an expression is a null pointer constant
+fn is_null(text: &str) -> bool {
+	return matches!(text.trim(), "NULL" | "nullptr" | "0");
+}
+
+/// `variable` as seen from `data_struct`, if its type is the handle of the DataStruct:
+/// with a `Graph*` handle, `Graph** p` is a pointer to a `Graph`
+pub fn handled_variable(variable: &ProgramVariable, data_struct: &DataStruct) -> Option<ProgramVariable> {
+	let handle = &data_struct.handle;
+	if variable.var_type != handle.type_name || variable.pointer_depth < handle.pointer_depth {
+		return None;
+	}
+	let mut handled = variable.clone();
+	handled.var_type = data_struct.name.clone();
+	handled.pointer_depth -= handle.pointer_depth;
+	return Some(handled);
+}
+
+fn lifecycle_rule(data_struct: &DataStruct, name: &str, call: &str, severity: Severity, message: String, hint: String) -> Rule {
+	return Rule {
+		id: format!("{}.{}", data_struct.name, name),
+		data_struct: data_struct.name.clone(),
+		call: call.to_string(),
+		severity,
+		message,
+		hint: Some(hint),
+		docs: None,
+	};
+}
+
+/// The rules every DataStruct with constructors or destructors follows without its guard having to say so,
+/// the ones of the DataStructs handled through a pointer and the one of the DataStructs marked `NoCopy`
+fn lifecycle_rules(data_struct: &DataStruct, options: &Options) -> Vec<Rule> {
+	let mut rules = Vec::new();
+	let name = &data_struct.name;
+	let constructors = data_struct.constructors.join(", ");
+	let destructors = data_struct.destructors.join(", ");
+	if !data_struct.constructors.is_empty() {
+		rules.push(lifecycle_rule(
+			data_struct,
+			"use-before-construct",
+			&constructors,
+			Severity::Error,
+			format!("{} used before being constructed", name),
+			format!("Construct it first with {}", constructors),
+		));
+	}
+	if !data_struct.destructors.is_empty() && options.use_after_destroy {
+		rules.push(lifecycle_rule(
+			data_struct,
+			"use-after-destroy",
+			&destructors,
+			Severity::Error,
+			format!("{} used after being destroyed", name),
+			format!("Do not use it once it has been given to {}", destructors),
+		));
+		rules.push(lifecycle_rule(
+			data_struct,
+			"double-destroy",
+			&destructors,
+			Severity::Error,
+			format!("{} destroyed twice", name),
+			format!("Give it only once to {}", destructors),
+		));
+	}
+	if !data_struct.destructors.is_empty() && options.leaks {
+		rules.push(lifecycle_rule(
+			data_struct,
+			"leak",
+			&destructors,
+			Severity::Warning,
+			format!("{} never destroyed", name),
+			format!("Destroy it with {} before it goes out of scope", destructors),
+		));
+	}
+	if data_struct.handle.kind == HandleKind::Pointer {
+		rules.push(lifecycle_rule(
+			data_struct,
+			"null",
+			&data_struct.handle.type_name,
+			Severity::Error,
+			format!("{} used while NULL", name),
+			"Give it an object before using it".to_string(),
+		));
+	}
+	if data_struct.handle.nullable && !data_struct.constructors.is_empty() {
+		rules.push(lifecycle_rule(
+			data_struct,
+			"unchecked-null",
+			&constructors,
+			Severity::Warning,
+			format!("{} not checked against NULL", name),
+			format!("Check that {} did not return NULL before using it", constructors),
+		));
+	}
+	if escape_policy(data_struct, options) == Escape::Warn {
+		rules.push(lifecycle_rule(
+			data_struct,
+			"escape",
+			"Escapes",
+			Severity::Warning,
+			format!("{} given to an unknown function", name),
+			"Describe the function in the guard, or check its source with the one calling it".to_string(),
+		));
+	}
+	if data_struct.no_copy {
+		rules.push(lifecycle_rule(
+			data_struct,
+			"copy",
+			"NoCopy",
+			Severity::Warning,
+			format!("{} copied", name),
+			"Use a pointer to it instead".to_string(),
+		));
+	}
+	return rules;
+}
+
+/// Every rule declared by the guards, in the order they are written, followed by the lifecycle rules of each DataStruct
+pub fn guard_rules(guards: &[DataStruct], options: &Options) -> Vec<Rule> {
+	let mut rules = Vec::new();
+	for guard in guards {
+		for call in &guard.calls {
+			for (warnings, kind, severity) in call_rules(call) {
+				for (i, warning) in warnings.iter().enumerate() {
+					rules.push(rule(guard, call, warning, kind, i, severity));
+				}
+			}
+		}
+		rules.extend(lifecycle_rules(guard, options));
+	}
+	return rules;
+}
+
+/// The severity and the message of a rule broken on every path or on some of them only,
+/// None if it should not be reported
+fn broken_rule(severity: Severity, message: String, always: bool, options: &Options) -> Option<(Severity, String)> {
+	if always {
+		return Some((severity, message));
+	}
+	let may_severity = options.may_severity?;
+	return Some((may_severity.min(severity), format!("{} on some paths", message)));
+}
+
+/// Evaluates the tests of `warnings` against `state` and reports the ones that hold on some path
+#[allow(clippy::too_many_arguments)]
+fn check_rules(
+	warnings: &[Warning], kind: &str, severity: Severity, data_struct: &DataStruct, call: &Call, variable: Option<&str>, state: &mut State,
+	location: &Location, options: &Options,
+) -> Vec<Diagnostic> {
+	let mut diagnostics = Vec::new();
+	for (i, warning) in warnings.iter().enumerate() {
+		let truth = state.environment.truth(&warning.test);
+		if truth == Truth::Never {
+			continue;
+		}
+		let rule = rule(data_struct, call, warning, kind, i, severity);
+		let Some((severity, message)) = broken_rule(severity, rule.message, truth == Truth::Always, options)
+		else {
+			continue;
+		};
+		diagnostics.push(Diagnostic {
+			rule_id: rule.id,
+			severity,
+			message,
+			hint: rule.hint,
+			data_struct: data_struct.name.clone(),
+			variable: variable.map(|name| name.to_string()),
+			meta: state.environment.to_assocs(),
+			location: location.clone(),
+			related: state.related(&expression_variables(&warning.test)),
+			path: Vec::new(),
+		});
+	}
+	return diagnostics;
+}
+
+/// Runs the assignments of a `Does` on the state of `variable`, what they set was set at `location`
+fn apply_does(state: &mut State, does: &[Assignement], location: &Location, variable: Option<&str>) {
+	for assignement in does {
+		state.environment.evaluate_assignement(assignement.clone());
+		let value = state.environment.fetch(&assignement.name);
+		let related = RelatedLocation {
+			location: location.clone(),
+			message: format!("`{}` of `{}` set to {} here", assignement.name, variable.unwrap_or_default(), value),
+		};
+		state.provenance.retain(|(name, _)| name != &assignement.name);
+		state.provenance.push((assignement.name.clone(), related));
+	}
+}
+
+/// `state` once `call` has had any of the outcomes it can have when what it returns passes `test`
+fn outcome_state(state: &State, call: &Call, test: Option<(Operator, i32)>, location: &Location, variable: Option<&str>) -> State {
+	return call
+		.outcomes(test)
+		.into_iter()
+		.map(|outcome| {
+			let mut state = state.clone();
+			if let Some(outcome) = outcome {
+				apply_does(&mut state, &outcome.does, location, variable);
+			}
+			return state;
+		})
+		.reduce(|state, other| state.join(&other))
+		.unwrap();
+}
+
+/// What changes the state of the tracked variables, in the order it is evaluated
+enum Event<'a> {
+	Call(&'a CallSite),
+	/// The initializer of a tracked variable, (start_byte, end_byte) of its value
+	Initialization {
+		index: usize,
+		value: (usize, usize),
+	},
+	Assignment(&'a Assignment),
+	/// (start_byte, end_byte) of the `return` statement
+	Return((usize, usize)),
+	NullCheck(&'a NullCheck),
+	/// The end of the scope of a tracked variable
+	ScopeEnd {
+		index: usize,
+		end_byte: usize,
+	},
+	/// A local variable followed in path-sensitive mode given a value, `operator` is `=`, `+=`, `++`...
+	Local {
+		name: &'a str,
+		operator: &'a str,
+		/// (start_byte, end_byte) of the value and of the whole expression
+		value: (usize, usize),
+		range: (usize, usize),
+	},
+}
+
+impl Event<'_> {
+	fn range(&self) -> (usize, usize) {
+		match self {
+			Event::Call(call) => (call.start_byte, call.end_byte),
+			Event::Initialization { value, .. } => *value,
+			Event::Assignment(assignment) => (assignment.start_byte, assignment.end_byte),
+			Event::Return(range) => *range,
+			Event::NullCheck(check) => (check.start_byte, check.end_byte),
+			Event::ScopeEnd { end_byte, .. } => (*end_byte, *end_byte),
+			Event::Local { range, .. } => *range,
+		}
+	}
+
+	/// Events of the same statement are evaluated in the order they end, the arguments of a call before the call
+	fn range_key(&self) -> (usize, usize) {
+		let (start, end) = self.range();
+		return (end, start);
+	}
+}
+
+/// How many times the start of a loop is followed before the number meta data still changing become unknown
+const WIDENING_DELAY: usize = 3;
+
+/// The most times a block is followed, in case what a loop starts with would never stop changing
+const MAX_VISITS: usize = 100;
+
+/// How many times a path goes through a loop in path-sensitive mode, the last time without knowing the local variables it changes
+pub const PATH_ITERATIONS: usize = 2;
+
+/// A call with outcomes made in a condition, whose outcome depends on the branch taken
+struct PendingOutcome<'a> {
+	variable: usize,
+	object: usize,
+	call: &'a Call,
+	/// The test its returned value passes when the condition holds
+	test: (Operator, i32),
+	location: Location,
+}
+
+/// A path followed on its own in path-sensitive mode, up to the start of `block`
+struct Path {
+	block: usize,
+	cells: Slots<Value>,
+	objects: Slots<State>,
+	locals: AbstractEnvironment,
+	assumptions: Vec<(EvaluableExpr, bool)>,
+	/// How many times the path went through each block
+	visits: Vec<usize>,
+	/// The lines of the statements run, a line is not repeated right after itself
+	lines: Vec<usize>,
+}
+
+/// What a local variable of type `var_type` can be when nothing is known of it, for the bool and integer types
+fn unknown_local(var_type: &str) -> Option<AbstractValue> {
+	const INTEGER_WORDS: [&str; 16] = [
+		"char", "short", "int", "long", "signed", "unsigned", "size_t", "ssize_t", "int8_t", "int16_t", "int32_t", "int64_t", "uint8_t",
+		"uint16_t", "uint32_t", "uint64_t",
+	];
+	match var_type {
+		"bool" | "_Bool" => return Some(AbstractValue::OneOf(vec![Constant::Bool(true), Constant::Bool(false)])),
+		var_type if var_type.split_whitespace().all(|word| INTEGER_WORDS.contains(&word)) => {
+			return Some(AbstractValue::Interval { min: None, max: None });
+		}
+		_ => return None,
+	}
+}
+
+/// Follows the tracked variables of a source file through its events
+struct Checker<'a> {
+	guards: &'a [DataStruct],
+	options: &'a Options,
+	file: &'a str,
+	source_code: &'a str,
+	tree: &'a Tree,
+	calls: &'a [CallSite],
+	/// The name of the function checked
+	entry_point: &'a str,
+	tracked: Vec<ProgramVariable>,
+	/// What each tracked variable holds, in the same order, followed by the memory allocated by constructors returning pointers
+	cells: Slots<Value>,
+	objects: Slots<State>,
+	/// Where each object and each cell after the ones of the variables is created
+	object_sites: Vec<Site>,
+	cell_sites: Vec<Site>,
+	timelines: Vec<Timeline>,
+	diagnostics: Vec<Diagnostic>,
+	/// The local variables followed in path-sensitive mode, with what they can be when nothing is known of them
+	unknown_locals: AbstractEnvironment,
+	/// What they can be on the path followed, and the conditions it took
+	locals: AbstractEnvironment,
+	assumptions: Vec<(EvaluableExpr, bool)>,
+	/// The calls of the conditions whose returned value is compared to a constant, with the test it passes when the condition holds
+	returned_tests: Vec<((usize, usize), (Operator, i32))>,
+	/// The calls with outcomes of the condition ending the block run, to apply on each branch
+	pending: Vec<PendingOutcome<'a>>,
+}
+
+impl<'a> Checker<'a> {
+	/// The object created at `site`, overwriting what a previous iteration created there
+	fn allocate(&mut self, site: Site, state: State) -> usize {
+		let object = match self.object_sites.iter().position(|&other| other == site) {
+			Some(object) => object,
+			None => {
+				self.object_sites.push(site);
+				self.object_sites.len() - 1
+			}
+		};
+		self.objects.set(object, state);
+		return object;
+	}
+
+	fn allocate_cell(&mut self, site: Site, value: Value) -> usize {
+		let index = match self.cell_sites.iter().position(|&other| other == site) {
+			Some(index) => index,
+			None => {
+				self.cell_sites.push(site);
+				self.cell_sites.len() - 1
+			}
+		};
+		let cell = self.tracked.len() + index;
+		self.cells.set(cell, value);
+		return cell;
+	}
+
+	fn guard(&self, variable: usize) -> &'a DataStruct {
+		let guards = self.guards;
+		return guards.iter().find(|guard| guard.name == self.tracked[variable].var_type).unwrap();
+	}
+
+	/// The cell reached from `variable` through `dereferences` `*` or `[]`, the elements of an array share its cell
+	fn cell(&self, variable: usize, dereferences: usize) -> Option<usize> {
+		let mut cell = variable;
+		for i in 0..dereferences {
+			if i < self.tracked[variable].array_dimensions {
+				continue;
+			}
+			match self.cells[cell] {
+				Value::Pointer(pointed) => cell = pointed,
+				_ => return None,
+			}
+		}
+		return Some(cell);
+	}
+
+	/// The object `variable` holds or points to
+	fn object(&self, variable: usize) -> Option<usize> {
+		let tracked = &self.tracked[variable];
+		let cell = self.cell(variable, tracked.pointer_depth + tracked.array_dimensions)?;
+		match self.cells[cell] {
+			Value::Object(object) => return Some(object),
+			_ => return None,
+		}
+	}
+
+	/// What `variable` with its address taken `indirection` times evaluates to, e.g. `&vec` or `*p`
+	fn value(&self, variable: usize, indirection: isize) -> Value {
+		match indirection {
+			1 => return Value::Pointer(variable),
+			indirection if indirection > 1 => return Value::Unknown,
+			_ => {}
+		}
+		let dereferences = (-indirection) as usize;
+		let Some(cell) = self.cell(variable, dereferences)
+		else {
+			return Value::Unknown;
+		};
+		// an array used as a value is the address of its elements
+		if dereferences < self.tracked[variable].array_dimensions {
+			return Value::Pointer(cell);
+		}
+		return self.cells[cell];
+	}
+
+	/// The object a value holds or points to
+	fn pointee(&self, value: Value) -> Option<usize> {
+		match value {
+			Value::Object(object) => return Some(object),
+			Value::Pointer(cell) => match self.cells[cell] {
+				Value::Object(object) => return Some(object),
+				_ => return None,
+			},
+			Value::Null | Value::Unknown => return None,
+		}
+	}
+
+	/// Whether `variable` is, or goes through, a pointer set to NULL before reaching its object
+	fn is_null(&self, variable: usize) -> bool {
+		let tracked = &self.tracked[variable];
+		let mut cell = variable;
+		for i in 0..tracked.pointer_depth + tracked.array_dimensions {
+			if i < tracked.array_dimensions {
+				continue;
+			}
+			match self.cells[cell] {
+				Value::Pointer(pointed) => cell = pointed,
+				Value::Null => return true,
+				_ => return false,
+			}
+		}
+		return self.cells[cell] == Value::Null;
+	}
+
+	/// Whether a variable for which `alive` holds still reaches `object`
+	fn reachable(&self, object: usize, alive: impl Fn(&ProgramVariable) -> bool) -> bool {
+		return (0..self.tracked.len()).any(|variable| alive(&self.tracked[variable]) && self.object(variable) == Some(object));
+	}
+
+	/// Adds the state of `object` to the timeline of every variable reaching it
+	fn record_change(&mut self, object: usize, location: &Location, start_byte: usize, end_byte: usize) {
+		for variable in 0..self.tracked.len() {
+			if !in_scope(&self.tracked[variable], start_byte) || self.object(variable) != Some(object) {
+				continue;
+			}
+			self.timelines[variable].changes.push(StateChange {
+				location: location.clone(),
+				call: self.source_code[start_byte..end_byte].to_string(),
+				meta: self.objects[object].environment.to_assocs(),
+			});
+		}
+	}
+
+	fn related(&self, variable: usize, message: &str, start_byte: usize, end_byte: usize) -> RelatedLocation {
+		return RelatedLocation {
+			location: Location::from_bytes(self.file, self.source_code, start_byte, end_byte),
+			message: format!("`{}` {}", self.tracked[variable].name, message),
+		};
+	}
+
+	/// Reports a lifecycle rule, `name` is the rule id without the DataStruct, e.g. `use-before-construct`,
+	/// `always` if it is broken on every path
+	#[allow(clippy::too_many_arguments)]
+	fn lifecycle_diagnostic(
+		&mut self, guard: &DataStruct, name: &str, variable: usize, object: Option<usize>, message: &str, always: bool, location: &Location,
+	) {
+		let id = format!("{}.{}", guard.name, name);
+		// turned off by the options
+		let Some(rule) = lifecycle_rules(guard, self.options).into_iter().find(|rule| rule.id == id)
+		else {
+			return;
+		};
+		let message = format!("`{}` {}", self.tracked[variable].name, message);
+		let Some((severity, message)) = broken_rule(rule.severity, message, always, self.options)
+		else {
+			return;
+		};
+		// a NULL handle has neither meta data nor history
+		let state = object.map(|object| &self.objects[object]);
+		self.diagnostics.push(Diagnostic {
+			rule_id: rule.id,
+			severity,
+			message,
+			hint: rule.hint,
+			data_struct: guard.name.clone(),
+			variable: Some(self.tracked[variable].name.clone()),
+			meta: state.map(|state| state.environment.to_assocs()).unwrap_or_default(),
+			location: location.clone(),
+			related: state.and_then(|state| state.lifecycle_provenance.clone()).into_iter().collect(),
+			path: Vec::new(),
+		});
+	}
+
+	/// Checks that the object a call is made on, or a destructor is given, has been constructed
+	fn check_constructed(&mut self, guard: &DataStruct, variable: usize, object: usize, location: &Location) {
+		let state = &self.objects[object];
+		if !state.may_be(Lifecycle::Unconstructed) {
+			return;
+		}
+		let always = state.must_be(Lifecycle::Unconstructed);
+		let message = "is used before being constructed";
+		self.lifecycle_diagnostic(guard, "use-before-construct", variable, Some(object), message, always, location);
+		// reported once, the calls after it are checked as if it had been constructed
+		let state = &mut self.objects[object];
+		state.lifecycle.retain(|&lifecycle| lifecycle != Lifecycle::Unconstructed);
+		if !state.may_be(Lifecycle::Alive) {
+			state.lifecycle.push(Lifecycle::Alive);
+		}
+	}
+
+	fn check_not_destroyed(&mut self, guard: &DataStruct, variable: usize, object: usize, location: &Location) {
+		let state = &self.objects[object];
+		if state.may_be(Lifecycle::Destroyed) {
+			let always = state.must_be(Lifecycle::Destroyed);
+			let message = "is used after being destroyed";
+			self.lifecycle_diagnostic(guard, "use-after-destroy", variable, Some(object), message, always, location);
+		}
+	}
+
+	/// Reports a call made on an object its constructor may have failed to create
+	fn check_null_checked(&mut self, guard: &DataStruct, variable: usize, object: usize, location: &Location) {
+		if !self.objects[object].may_be_null {
+			return;
+		}
+		let message = "may be NULL, it is not checked after being constructed";
+		self.lifecycle_diagnostic(guard, "unchecked-null", variable, Some(object), message, true, location);
+		// reported once
+		self.objects[object].may_be_null = false;
+	}
+
+	fn destroy(&mut self, guard: &DataStruct, variable: usize, object: usize, call_site: &CallSite, location: &Location) {
+		self.check_constructed(guard, variable, object, location);
+		let state = &self.objects[object];
+		if state.may_be(Lifecycle::Destroyed) {
+			let always = state.must_be(Lifecycle::Destroyed);
+			self.lifecycle_diagnostic(
+				guard,
+				"double-destroy",
+				variable,
+				Some(object),
+				"is destroyed twice",
+				always,
+				location,
+			);
+			if always {
+				return;
+			}
+		}
+		// one element of an array being destroyed does not destroy the others
+		if self.objects[object].summary {
+			return;
+		}
+		let related = self.related(variable, "destroyed here", call_site.start_byte, call_site.end_byte);
+		let state = &mut self.objects[object];
+		state.lifecycle = vec![Lifecycle::Destroyed];
+		state.lifecycle_provenance = Some(related);
+		state.owned = false;
+		self.record_change(object, location, call_site.start_byte, call_site.end_byte);
+	}
+
+	/// Reports `object` if it is owned and still alive, when nothing can destroy it anymore
+	fn check_leak(&mut self, variable: usize, object: usize, message: &str, location: &Location) {
+		let state = &self.objects[object];
+		if !state.may_be(Lifecycle::Alive) || !state.owned {
+			return;
+		}
+		let always = state.must_be(Lifecycle::Alive);
+		self.lifecycle_diagnostic(self.guard(variable), "leak", variable, Some(object), message, always, location);
+		// reported once
+		self.objects[object].owned = false;
+	}
+
+	fn return_statement(&mut self, (start_byte, end_byte): (usize, usize)) {
+		let location = Location::from_bytes(self.file, self.source_code, start_byte, end_byte);
+		for variable in 0..self.tracked.len() {
+			if !in_scope(&self.tracked[variable], start_byte) {
+				continue;
+			}
+			if let Some(object) = self.object(variable) {
+				let message = format!("is not destroyed before `{}` returns", self.entry_point);
+				self.check_leak(variable, object, &message, &location);
+			}
+		}
+	}
+
+	fn scope_end(&mut self, variable: usize, end_byte: usize) {
+		let Some(object) = self.object(variable)
+		else {
+			return;
+		};
+		// still held by a variable of an enclosing scope
+		if self.reachable(object, |other| other.declaration.0 <= end_byte && end_byte < other.scope.1) {
+			return;
+		}
+		// the closing bracket
+		let location = Location::from_bytes(self.file, self.source_code, end_byte - 1, end_byte);
+		self.check_leak(variable, object, "goes out of scope without being destroyed", &location);
+	}
+
+	/// The tracked variable and its object a call is made on.
+	/// A call made on NULL is reported, unless it is a destructor, which are expected to do nothing with it
+	fn bind(&mut self, guard: &DataStruct, call: &Call, call_site: &CallSite, destructor: bool) -> Option<(usize, usize)> {
+		let variable = bind_self(&self.tracked, call, call_site, &guard.name)?;
+		let Some(object) = self.object(variable)
+		else {
+			if !self.is_null(variable) {
+				debug!("`{}` does not point to a known {}", self.tracked[variable].name, guard.name);
+			}
+			else if !destructor {
+				let location = location(self.file, call_site);
+				self.lifecycle_diagnostic(guard, "null", variable, None, "is NULL here", true, &location);
+			}
+			return None;
+		};
+		return Some((variable, object));
+	}
+
+	fn call(&mut self, call_site: &CallSite) {
+		let location = location(self.file, call_site);
+		if !summary::described(self.guards, &call_site.function) {
+			self.escape(call_site, &location);
+		}
+		for guard in self.guards {
+			for call in guard.calls.iter().filter(|call| call.function_name() == call_site.function) {
+				// calls without $self are checked against an empty state
+				let (bound, mut state) = match call.self_position() {
+					Some(_) => match self.bind(guard, call, call_site, false) {
+						Some((variable, object)) => {
+							self.check_constructed(guard, variable, object, &location);
+							self.check_null_checked(guard, variable, object, &location);
+							// a destructor given a destroyed object destroys it twice, which `destroy` reports
+							if !guard.destructors.iter().any(|destructor| destructor == call.signature()) {
+								self.check_not_destroyed(guard, variable, object, &location);
+							}
+							(Some((variable, object)), self.objects[object].clone())
+						}
+						None => continue,
+					},
+					None => (None, State::new(AbstractEnvironment::new())),
+				};
+				let variable = bound.map(|(variable, _)| self.tracked[variable].name.clone());
+
+				for (warnings, kind, severity) in call_rules(call) {
+					let variable = variable.as_deref();
+					let diagnostics = check_rules(warnings, kind, severity, guard, call, variable, &mut state, &location, self.options);
+					self.diagnostics.extend(diagnostics);
+				}
+
+				apply_does(&mut state, call.does.as_deref().unwrap_or_default(), &location, variable.as_deref());
+				if let (Some((variable, object)), Some(_)) = (bound, &call.returns) {
+					let range = (call_site.start_byte, call_site.end_byte);
+					match self.returned_tests.iter().find(|(call, _)| *call == range) {
+						// applied on each branch of the condition
+						Some(&(_, test)) => self.pending.push(PendingOutcome {
+							variable,
+							object,
+							call,
+							test,
+							location: location.clone(),
+						}),
+						None => state = outcome_state(&state, call, None, &location, Some(&self.tracked[variable].name)),
+					}
+				}
+				if let Some((_, object)) = bound {
+					self.objects[object] = state;
+					self.record_change(object, &location, call_site.start_byte, call_site.end_byte);
+				}
+			}
+			// the rules of a destructor are checked before it destroys
+			for destructor in guard
+				.destructor_calls()
+				.iter()
+				.filter(|call| call.function_name() == call_site.function)
+			{
+				if let Some((variable, object)) = self.bind(guard, destructor, call_site, true) {
+					self.destroy(guard, variable, object, call_site, &location);
+				}
+			}
+		}
+	}
+
+	/// A call to a function no guard or summary describes, which can do anything to the objects given to it
+	fn escape(&mut self, call_site: &CallSite, location: &Location) {
+		for argument in &call_site.arguments {
+			let Some(place) = &argument.place
+			else {
+				continue;
+			};
+			for variable in 0..self.tracked.len() {
+				let tracked = &self.tracked[variable];
+				// the object itself, a copy sharing it or a pointer to it
+				let depth = (tracked.pointer_depth + tracked.array_dimensions) as isize;
+				let visible = visible_variable(&self.tracked, &place.variable, &tracked.var_type, call_site.start_byte);
+				if visible != Some(variable) || depth + place.indirection < 0 {
+					continue;
+				}
+				let Some(object) = self.object(variable)
+				else {
+					continue;
+				};
+				let guard = self.guard(variable);
+				match escape_policy(guard, self.options) {
+					Escape::Havoc => {
+						let name = self.tracked[variable].name.clone();
+						let state = &mut self.objects[object];
+						for meta in &guard.meta_data {
+							state.environment.set(&meta.name, AbstractValue::Unknown);
+							let related = RelatedLocation {
+								location: location.clone(),
+								message: format!("`{}` of `{}` unknown after `{}` here", meta.name, name, call_site.function),
+							};
+							state.provenance.retain(|(other, _)| *other != meta.name);
+							state.provenance.push((meta.name.clone(), related));
+						}
+						self.record_change(object, location, call_site.start_byte, call_site.end_byte);
+					}
+					Escape::Keep => {}
+					Escape::Warn => {
+						let message = format!("escapes into unknown function `{}`", call_site.function);
+						self.lifecycle_diagnostic(guard, "escape", variable, Some(object), &message, true, location);
+					}
+				}
+			}
+		}
+	}
+
+	/// A new object with the default meta data, constructed by `constructor` for `variable`
+	fn construct(&mut self, guard: &DataStruct, variable: usize, constructor: &CallSite, statement: (usize, usize)) -> usize {
+		let location = Location::from_bytes(self.file, self.source_code, statement.0, statement.1);
+		let mut state = State::new(AbstractEnvironment::from_meta(&guard.meta_data));
+		for meta in &guard.meta_data {
+			let related = RelatedLocation {
+				location: location.clone(),
+				message: format!(
+					"`{}` of `{}` set to {} by `{}` here",
+					meta.name,
+					self.tracked[variable].name,
+					state.environment.fetch(&meta.name),
+					constructor.function
+				),
+			};
+			state.provenance.push((meta.name.clone(), related));
+		}
+		state.lifecycle_provenance = Some(self.related(variable, "constructed here", statement.0, statement.1));
+		state.summary = self.tracked[variable].array_dimensions > 0;
+		state.owned = !state.summary;
+		return self.allocate(
+			Site::Store {
+				variable,
+				statement: statement.0,
+			},
+			state,
+		);
+	}
+
+	/// The tracked variable an expression designates, with how many times its address is taken,
+	/// if its type is the one expected
+	fn source_variable(&self, guard: &DataStruct, value: (usize, usize), depth: usize) -> Option<(usize, isize)> {
+		let place = ast::place_at(self.tree.root_node(), self.source_code, value)?;
+		let source = visible_variable(&self.tracked, &place.variable, &guard.name, value.0)?;
+		let variable = &self.tracked[source];
+		if (variable.pointer_depth + variable.array_dimensions) as isize + place.indirection != depth as isize {
+			return None;
+		}
+		return Some((source, place.indirection));
+	}
+
+	/// `variable` gone through `dereferences` times is given the value spanning `value`.
+	/// A constructor gives it a new object, and leaks the previous one if nothing else holds it.
+	/// A variable (`b = a`, `p = &vec`) makes it share the object of that variable.
+	/// Anything else is taken as an object that has been constructed elsewhere
+	fn store(&mut self, variable: usize, dereferences: usize, value: (usize, usize), statement: (usize, usize), initialization: bool) {
+		let guard = self.guard(variable);
+		let tracked = &self.tracked[variable];
+		let Some(depth) = (tracked.pointer_depth + tracked.array_dimensions).checked_sub(dereferences)
+		else {
+			return;
+		};
+		let Some(cell) = self.cell(variable, dereferences)
+		else {
+			return;
+		};
+		let location = Location::from_bytes(self.file, self.source_code, statement.0, statement.1);
+		let calls = self.calls;
+		let constructor = calls
+			.iter()
+			.find(|call| (call.start_byte, call.end_byte) == value && guard.is_constructor(&call.function));
+		let new_value = if let Some(constructor) = constructor {
+			let object = self.construct(guard, variable, constructor, statement);
+			// a constructor returning the handle itself may return NULL
+			self.objects[object].may_be_null = depth == 0 && guard.handle.nullable;
+			match depth {
+				0 => Value::Object(object),
+				1 => {
+					let site = Site::Store {
+						variable,
+						statement: statement.0,
+					};
+					Value::Pointer(self.allocate_cell(site, Value::Object(object)))
+				}
+				_ => Value::Unknown,
+			}
+		}
+		else if is_null(&self.source_code[value.0..value.1]) && (depth > 0 || guard.handle.kind == HandleKind::Pointer) {
+			Value::Null
+		}
+		else if let Some((source, indirection)) = self.source_variable(guard, value, depth) {
+			let new_value = self.value(source, indirection);
+			if let (0, Value::Object(object)) = (depth, new_value) {
+				if guard.no_copy {
+					let message = format!("is a copy of `{}`, {} must not be copied", self.tracked[source].name, guard.name);
+					self.lifecycle_diagnostic(guard, "copy", variable, Some(object), &message, true, &location);
+				}
+			}
+			new_value
+		}
+		else if depth == 0 {
+			let site = Site::Store {
+				variable,
+				statement: statement.0,
+			};
+			Value::Object(self.allocate(site, State::new(AbstractEnvironment::from_meta(&guard.meta_data))))
+		}
+		else {
+			Value::Unknown
+		};
+
+		let previous = self.pointee(self.cells[cell]);
+		self.cells[cell] = new_value;
+		if let Some(previous) = previous.filter(|&previous| Some(previous) != self.pointee(new_value)) {
+			if !self.reachable(previous, |other| in_scope(other, statement.0)) {
+				let message = "is given a new object before its previous one is destroyed";
+				self.check_leak(variable, previous, message, &location);
+			}
+		}
+
+		let Some(object) = self.object(variable)
+		else {
+			return;
+		};
+		if initialization {
+			self.timelines[variable].initial = self.objects[object].environment.to_assocs();
+		}
+		else {
+			self.record_change(object, &location, statement.0, statement.1);
+		}
+	}
+
+	fn assignment(&mut self, assignment: &Assignment) {
+		let Some(target) = assignment.target.as_ref().filter(|target| target.indirection <= 0)
+		else {
+			return;
+		};
+		if assignment.operator != "=" {
+			return;
+		}
+		for guard in self.guards {
+			if let Some(variable) = visible_variable(&self.tracked, &target.variable, &guard.name, assignment.start_byte) {
+				let statement = (assignment.start_byte, assignment.end_byte);
+				self.store(variable, (-target.indirection) as usize, assignment.value, statement, false);
+			}
+		}
+	}
+
+	/// A handle compared to NULL, `if (g)` or `g == NULL`, is not NULL on the path that uses it
+	fn null_check(&mut self, check: &NullCheck) {
+		for guard in self.guards {
+			let Some(variable) = visible_variable(&self.tracked, &check.place.variable, &guard.name, check.start_byte)
+			else {
+				continue;
+			};
+			if let Some(object) = self.object(variable) {
+				self.objects[object].may_be_null = false;
+			}
+		}
+	}
+
+	/// The expression spanning `range`, if it only uses the local variables followed
+	fn local_expression(&self, range: (usize, usize)) -> Option<EvaluableExpr> {
+		let expression = ast::expression_at(self.tree.root_node(), self.source_code, range)?;
+		let known = |name: &String| self.unknown_locals.assocs.iter().any(|(local, _)| local == name);
+		if !expression_variables(&expression).iter().all(known) {
+			return None;
+		}
+		return Some(expression);
+	}
+
+	/// A local variable followed in path-sensitive mode is given a new value, anything if it cannot be evaluated.
+	/// The conditions testing it no longer tell anything
+	fn local(&mut self, name: &str, operator: &str, value: (usize, usize)) {
+		let current = Token::Operand(Identifier::var(name));
+		let one = Token::Operand(Identifier::const_num(1));
+		let tokens = match operator {
+			"=" => self.local_expression(value),
+			"++" => Some(vec![current, one, Token::Operation(Operator::Addition)]),
+			"--" => Some(vec![current, one, Token::Operation(Operator::Substraction)]),
+			// `+=`, `-=`...
+			operator => match (self.local_expression(value), Operator::from_string(operator.trim_end_matches('='))) {
+				(Some(expression), Ok(operation)) => {
+					let mut tokens = vec![current];
+					tokens.extend(expression);
+					tokens.push(Token::Operation(operation));
+					Some(tokens)
+				}
+				_ => None,
+			},
+		};
+		let value = match tokens {
+			Some(tokens) => self.locals.evaluate(&tokens),
+			None => self.unknown_locals.fetch(name),
+		};
+		self.locals.set(name, value);
+		self.assumptions
+			.retain(|(test, _)| !expression_variables(test).iter().any(|variable| variable == name));
+	}
+
+	fn event(&mut self, event: &Event) {
+		match *event {
+			Event::Call(call_site) => self.call(call_site),
+			Event::Initialization { index, value } => {
+				let declaration = self.tracked[index].declaration;
+				self.store(index, 0, value, declaration, true);
+			}
+			Event::Assignment(assignment) => self.assignment(assignment),
+			Event::Return(range) => self.return_statement(range),
+			Event::NullCheck(check) => self.null_check(check),
+			Event::ScopeEnd { index, end_byte } => self.scope_end(index, end_byte),
+			Event::Local { name, operator, value, .. } => self.local(name, operator, value),
+		}
+	}
+
+	/// `file:line` of the first statement of a block, for the messages about the analysis
+	fn block_position(&self, cfg: &Cfg, block: usize) -> String {
+		match cfg.blocks[block].statements.first() {
+			Some(&(start, end)) => {
+				let location = Location::from_bytes(self.file, self.source_code, start, end);
+				return format!("{}:{}", self.file, location.line);
+			}
+			None => return self.file.to_string(),
+		}
+	}
+
+	/// The objects on the branch where the condition ending the block run holds, or does not,
+	/// once the calls it tests have had the outcomes matching what they returned
+	fn branch_objects(&self, holds: bool) -> Slots<State> {
+		let mut objects = self.objects.clone();
+		for pending in &self.pending {
+			let (op, value) = pending.test;
+			let test = if holds { (op, value) } else { (op.negation(), value) };
+			let variable = Some(self.tracked[pending.variable].name.as_str());
+			objects[pending.object] = outcome_state(&objects[pending.object], pending.call, Some(test), &pending.location, variable);
+		}
+		return objects;
+	}
+
+	/// The objects on the edge from `block` to `successor`, see [`Checker::branch_objects`]
+	fn edge_objects(&self, cfg: &Cfg, block: usize, successor: usize) -> Slots<State> {
+		match cfg.blocks[block].branch {
+			Some(branch) if !self.pending.is_empty() => return self.branch_objects(successor == branch.taken),
+			_ => return self.objects.clone(),
+		}
+	}
+
+	/// Runs the events of the blocks reached from the entry until what they start with stops changing,
+	/// where paths meet the objects can be in any of the states they have on each of them.
+	/// Once a loop has been followed a few times, the number meta data still changing at its start become unknown.
+	/// The blocks are then run once more in the order of the source, from what they start with, to report what they break
+	fn run(&mut self, cfg: &Cfg, events: &[Vec<(usize, Event)>]) {
+		let mut entries: Vec<Option<(Slots<Value>, Slots<State>)>> = vec![None; cfg.blocks.len()];
+		entries[cfg.entry] = Some((self.cells.clone(), self.objects.clone()));
+		let loop_heads = cfg.loop_heads();
+		let mut visits = vec![0; cfg.blocks.len()];
+		let mut worklist = VecDeque::from([cfg.entry]);
+		while let Some(block) = worklist.pop_front() {
+			visits[block] += 1;
+			if visits[block] > MAX_VISITS {
+				verbose!(
+					"{}: stopped following a loop after {} iterations",
+					self.block_position(cfg, block),
+					MAX_VISITS
+				);
+				continue;
+			}
+			(self.cells, self.objects) = entries[block].clone().unwrap();
+			self.pending.clear();
+			for (_, event) in &events[block] {
+				self.event(event);
+			}
+			for &successor in &cfg.blocks[block].successors {
+				let edge_objects = self.edge_objects(cfg, block, successor);
+				let joined = match &entries[successor] {
+					Some((cells, objects)) if loop_heads[successor] && visits[successor] >= WIDENING_DELAY => {
+						let mut widened = Vec::new();
+						let objects = objects.join(&edge_objects, |state, other| {
+							let (state, names) = state.widen(other);
+							widened.extend(names);
+							return state;
+						});
+						for name in widened {
+							verbose!(
+								"{}: `{}` takes new values at each iteration of the loop, it is considered unknown",
+								self.block_position(cfg, successor),
+								name
+							);
+						}
+						(cells.join(&self.cells, join_values), objects)
+					}
+					Some((cells, objects)) => (cells.join(&self.cells, join_values), objects.join(&edge_objects, State::join)),
+					None => (self.cells.clone(), edge_objects),
+				};
+				if entries[successor].as_ref() != Some(&joined) {
+					entries[successor] = Some(joined);
+					if !worklist.contains(&successor) {
+						worklist.push_back(successor);
+					}
+				}
+			}
+		}
+
+		self.diagnostics.clear();
+		for timeline in &mut self.timelines {
+			timeline.changes.clear();
+		}
+		let mut blocks = (0..cfg.blocks.len()).filter(|&block| entries[block].is_some()).collect::<Vec<_>>();
+		blocks.sort_by_key(|&block| cfg.blocks[block].statements.first().copied());
+		for block in blocks {
+			(self.cells, self.objects) = entries[block].clone().unwrap();
+			self.pending.clear();
+			for (_, event) in &events[block] {
+				self.event(event);
+			}
+		}
+	}
+
+	/// Whether the condition `test` holds on the path followed, from the local variables and the conditions already taken
+	fn condition_truth(&self, test: &EvaluableExpr) -> Truth {
+		match self.assumptions.iter().find(|(assumed, _)| assumed == test) {
+			Some((_, true)) => return Truth::Always,
+			Some((_, false)) => return Truth::Never,
+			None => return self.locals.truth(test),
+		}
+	}
+
+	/// Follows the paths from the entry one by one, the branches testing local variables are only taken where they can be.
+	/// A path goes at most `PATH_ITERATIONS` times through a loop.
+	/// Returns what the paths break, each with the first path found breaking it, or None if there are more than the budget
+	fn run_paths(&mut self, cfg: &Cfg, events: &[Vec<(usize, Event)>]) -> Option<Vec<Diagnostic>> {
+		let loop_heads = cfg.loop_heads();
+		let conditions = cfg
+			.blocks
+			.iter()
+			.map(|block| block.branch.and_then(|branch| self.local_expression(branch.condition)))
+			.collect::<Vec<_>>();
+		// the local variables changed by the loop starting at each block
+		let loop_locals = (0..cfg.blocks.len())
+			.map(|head| {
+				if !loop_heads[head] {
+					return Vec::new();
+				}
+				let from_head = cfg.reached_from(head);
+				return (0..cfg.blocks.len())
+					.filter(|&block| from_head[block] && cfg.reached_from(block)[head])
+					.flat_map(|block| &events[block])
+					.filter_map(|(_, event)| match event {
+						Event::Local { name, .. } => Some(*name),
+						_ => None,
+					})
+					.collect::<Vec<_>>();
+			})
+			.collect::<Vec<_>>();
+
+		let mut found: Vec<Diagnostic> = Vec::new();
+		let mut paths = 1;
+		let mut stack = vec![Path {
+			block: cfg.entry,
+			cells: self.cells.clone(),
+			objects: self.objects.clone(),
+			locals: self.unknown_locals.clone(),
+			assumptions: Vec::new(),
+			visits: vec![0; cfg.blocks.len()],
+			lines: Vec::new(),
+		}];
+		while let Some(mut path) = stack.pop() {
+			let block = path.block;
+			path.visits[block] += 1;
+			if loop_heads[block] && path.visits[block] > PATH_ITERATIONS {
+				continue;
+			}
+			// the last time through the loop, it may leave whatever the local variables it changes hold
+			if loop_heads[block] && path.visits[block] == PATH_ITERATIONS {
+				for &name in &loop_locals[block] {
+					path.locals.set(name, self.unknown_locals.fetch(name));
+				}
+				path.assumptions.retain(|(test, _)| {
+					return !expression_variables(test)
+						.iter()
+						.any(|variable| loop_locals[block].contains(&variable.as_str()));
+				});
+			}
+			(self.cells, self.objects, self.locals, self.assumptions) = (path.cells, path.objects, path.locals, path.assumptions);
+			self.pending.clear();
+
+			let mut pending = events[block].iter().peekable();
+			for (statement, &(start, end)) in cfg.blocks[block].statements.iter().enumerate() {
+				// the closing brackets are not worth a line
+				if &self.source_code[start..end] != "}" {
+					let line = Location::from_bytes(self.file, self.source_code, start, end).line;
+					if path.lines.last() != Some(&line) {
+						path.lines.push(line);
+					}
+				}
+				while let Some((_, event)) = pending.next_if(|(event_statement, _)| *event_statement == statement) {
+					let before = self.diagnostics.len();
+					self.event(event);
+					for mut diagnostic in self.diagnostics.split_off(before) {
+						let same = |other: &Diagnostic| {
+							other.rule_id == diagnostic.rule_id
+								&& other.location == diagnostic.location
+								&& other.variable == diagnostic.variable
+						};
+						if !found.iter().any(same) {
+							diagnostic.path = path.lines.clone();
+							found.push(diagnostic);
+						}
+					}
+				}
+			}
+
+			let successors = match (cfg.blocks[block].branch, &conditions[block]) {
+				(Some(branch), Some(test)) => match self.condition_truth(test) {
+					Truth::Always => vec![(branch.taken, Some(true))],
+					Truth::Never => vec![(branch.not_taken, Some(false))],
+					Truth::Sometimes => vec![(branch.taken, Some(true)), (branch.not_taken, Some(false))],
+				},
+				(Some(branch), None) => vec![(branch.taken, Some(true)), (branch.not_taken, Some(false))],
+				(None, _) => cfg.blocks[block].successors.iter().map(|&successor| (successor, None)).collect(),
+			};
+			paths += successors.len().saturating_sub(1);
+			if paths > self.options.path_budget {
+				return None;
+			}
+			// the first successor is followed first
+			for &(successor, outcome) in successors.iter().rev() {
+				let mut locals = self.locals.clone();
+				let mut assumptions = self.assumptions.clone();
+				if let (Some(outcome), Some(test)) = (outcome, &conditions[block]) {
+					locals.assume(test, outcome);
+					assumptions.retain(|(assumed, _)| assumed != test);
+					assumptions.push((test.clone(), outcome));
+				}
+				stack.push(Path {
+					block: successor,
+					cells: self.cells.clone(),
+					objects: self.edge_objects(cfg, block, successor),
+					locals,
+					assumptions,
+					visits: path.visits.clone(),
+					lines: path.lines.clone(),
+				});
+			}
+		}
+		debug!("{}: followed {} paths", self.file, paths);
+		found.sort_by_key(|diagnostic| (diagnostic.location.end_byte, diagnostic.location.start_byte));
+		return Some(found);
+	}
+
+	/// Gives a new variable what it holds before its initializer runs,
+	/// an unconstructed object if it is a DataStruct with constructors and nothing if it is a pointer
+	fn declare(&mut self, variable: usize) {
+		let guard = self.guard(variable);
+		let tracked = &self.tracked[variable];
+		let environment = AbstractEnvironment::from_meta(&guard.meta_data);
+		let value = if tracked.pointer_depth == 0 {
+			let mut state = State::new(environment.clone());
+			state.summary = tracked.array_dimensions > 0;
+			if !guard.constructors.is_empty() {
+				state.lifecycle = vec![Lifecycle::Unconstructed];
+				let message = "declared here without being constructed";
+				state.lifecycle_provenance = Some(self.related(variable, message, tracked.declaration.0, tracked.declaration.1));
+			}
+			Value::Object(self.allocate(Site::Declaration(variable), state))
+		}
+		else {
+			Value::Unknown
+		};
+		self.cells.set(variable, value);
+		self.timelines
+			.push(timeline(self.file, self.source_code, &self.tracked[variable], &environment));
+	}
+
+	/// A parameter of the entry point, holding an object of its caller in any state
+	fn declare_parameter(&mut self, variable: usize) {
+		let guard = self.guard(variable);
+		let tracked = &self.tracked[variable];
+		let mut environment = AbstractEnvironment::from_meta(&guard.meta_data);
+		for meta in &guard.meta_data {
+			environment.set(&meta.name, AbstractValue::Unknown);
+		}
+		let mut state = State::new(environment.clone());
+		state.summary = tracked.array_dimensions > 0;
+		let depth = tracked.pointer_depth;
+		let mut value = Value::Object(self.allocate(Site::Parameter { variable, depth: 0 }, state));
+		for depth in 1..=depth {
+			value = Value::Pointer(self.allocate_cell(Site::Parameter { variable, depth }, value));
+		}
+		self.cells.set(variable, value);
+		self.timelines
+			.push(timeline(self.file, self.source_code, &self.tracked[variable], &environment));
+	}
+}
+
+/// Checks the calls made from the entry points of a C source file, `main` by default, against the guards
+pub fn check_source(guards: &[DataStruct], options: &Options, file: &str, source_code: &str) -> Report {
+	return check_linked_source(guards, &[], options, file, source_code);
+}
+
+/// Like [`check_source`], the functions of other files are the ones of `summaries`
+pub fn check_linked_source(
+	guards: &[DataStruct], summaries: &[summary::Summary], options: &Options, file: &str, source_code: &str,
+) -> Report {
+	let tree = ast::parse_source(source_code);
+	let facts = ast::extract(tree.root_node(), source_code);
+	// the functions of the source taking DataStructs become calls of their guards
+	let external = summary::external(summaries, &facts);
+	let summaries = summary::summarise(guards, &external, options, file, source_code, &tree, &facts);
+	debug!("SUMMARIES : {:#?}", summaries);
+	let guards = &summary::with_summaries(guards, &[external, summaries].concat());
+	let entry_points = facts
+		.functions
+		.iter()
+		.filter(|function| {
+			options
+				.entry_points
+				.iter()
+				.any(|pattern| ast::matches_pattern(pattern, &function.name))
+		})
+		.collect::<Vec<_>>();
+	let mut report = Report::new();
+	report.sources.push(SourceFile {
+		path: file.to_string(),
+		content: source_code.to_string(),
+	});
+	// the rules of the summaries too, which their diagnostics refer to
+	report.rules = guard_rules(guards, options);
+	// e.g. the other files of the program, which are only summarised
+	if entry_points.is_empty() {
+		verbose!("{}: no entry point to check", file);
+		return report;
+	}
+	for entry_point in entry_points {
+		report.extend(check_entry_point(guards, options, file, source_code, &tree, &facts, entry_point));
+	}
+	return report;
+}
+
+/// Checks the calls made from one entry point, on its own
+fn check_entry_point(
+	guards: &[DataStruct], options: &Options, file: &str, source_code: &str, tree: &Tree, facts: &SourceFacts,
+	entry_point: &FunctionDefinition,
+) -> Report {
+	let noreturn = noreturn_functions(options, facts);
+	let entry_range = (entry_point.start_byte, entry_point.end_byte);
+	let in_entry_point = |(start, end): (usize, usize)| entry_range.0 <= start && end <= entry_range.1;
+
+	// pointers stand for the object they point to and arrays for all of their elements,
+	// a variable is tracked once for every DataStruct it can be the handle of
+	let handled = |variable: &ProgramVariable| {
+		guards
+			.iter()
+			.filter_map(|guard| handled_variable(variable, guard))
+			.collect::<Vec<_>>()
+	};
+	let mut tracked: Vec<ProgramVariable> = entry_point.parameters.iter().flat_map(handled).collect();
+	let parameters = tracked.len();
+	tracked.extend(
+		facts
+			.variables
+			.iter()
+			.filter(|variable| in_entry_point(variable.scope))
+			.flat_map(handled),
+	);
+
+	let mut events: Vec<Event> = facts.calls.iter().map(Event::Call).collect();
+	events.extend(tracked.iter().enumerate().flat_map(|(index, variable)| {
+		return variable
+			.implications
+			.iter()
+			.map(move |&value| Event::Initialization { index, value });
+	}));
+	events.extend(facts.assignments.iter().map(Event::Assignment));
+	events.extend(facts.returns.iter().copied().map(Event::Return));
+	events.extend(facts.null_checks.iter().map(Event::NullCheck));
+	events.extend(tracked.iter().enumerate().map(|(index, variable)| Event::ScopeEnd {
+		index,
+		end_byte: variable.scope.1,
+	}));
+
+	// in path-sensitive mode, the local bool and integer variables that only assignments change,
+	// the ones whose address is taken or whose name is declared twice are not followed
+	let mut unknown_locals = AbstractEnvironment::new();
+	if options.path_sensitive {
+		let values = facts.assignments.iter().map(|assignment| assignment.value);
+		let values = values.chain(facts.variables.iter().flat_map(|variable| variable.implications.iter().copied()));
+		let addresses_taken = facts
+			.calls
+			.iter()
+			.flat_map(|call| call.arguments.iter().filter_map(|argument| argument.place.clone()))
+			.chain(values.filter_map(|value| ast::place_at(tree.root_node(), source_code, value)))
+			.filter(|place| place.indirection > 0)
+			.map(|place| place.variable)
+			.collect::<Vec<_>>();
+		let locals = facts.variables.iter().filter(|variable| {
+			return in_entry_point(variable.scope)
+				&& variable.pointer_depth == 0
+				&& variable.array_dimensions == 0
+				&& !tracked.iter().any(|tracked| tracked.declaration == variable.declaration)
+				&& facts.variables.iter().filter(|other| other.name == variable.name).count() == 1
+				&& !addresses_taken.contains(&variable.name);
+		});
+		for variable in locals {
+			let Some(value) = unknown_local(&variable.var_type)
+			else {
+				continue;
+			};
+			unknown_locals.set(&variable.name, value);
+			events.extend(variable.implications.iter().map(|&value| Event::Local {
+				name: &variable.name,
+				operator: "=",
+				value,
+				range: value,
+			}));
+			events.extend(
+				facts
+					.assignments
+					.iter()
+					.filter(|assignment| {
+						assignment
+							.target
+							.as_ref()
+							.is_some_and(|target| target.variable == variable.name && target.indirection == 0)
+					})
+					.map(|assignment| Event::Local {
+						name: &variable.name,
+						operator: &assignment.operator,
+						value: assignment.value,
+						range: (assignment.start_byte, assignment.end_byte),
+					}),
+			);
+		}
+	}
+	events.retain(|event| in_entry_point(event.range()));
+
+	// the events of each block, in the order of its statements
+	let body = tree
+		.root_node()
+		.descendant_for_byte_range(entry_point.body.0, entry_point.body.1)
+		.unwrap();
+	let cfg = cfg::build(body, source_code, &noreturn);
+	let mut block_events: Vec<Vec<(usize, Event)>> = cfg.blocks.iter().map(|_| Vec::new()).collect();
+	for event in events {
+		match cfg.statement_at(event.range()) {
+			Some((block, statement)) => block_events[block].push((statement, event)),
+			None => debug!("{:?} is not in a statement of `{}`", event.range(), entry_point.name),
+		}
+	}
+	for events in &mut block_events {
+		events.sort_by_key(|(statement, event)| (*statement, event.range_key()));
+	}
+	let returned_tests = cfg
+		.blocks
+		.iter()
+		.filter_map(|block| block.branch)
+		.flat_map(|branch| {
+			let (start, end) = branch.condition;
+			let condition = tree.root_node().descendant_for_byte_range(start, end).unwrap();
+			return facts
+				.calls
+				.iter()
+				.filter(move |call| start <= call.start_byte && call.end_byte <= end)
+				.filter_map(move |call| {
+					let range = (call.start_byte, call.end_byte);
+					return ast::returned_value_test(condition, source_code, range).map(|test| (range, test));
+				});
+		})
+		.collect();
+
+	let mut checker = Checker {
+		guards,
+		options,
+		file,
+		source_code,
+		tree,
+		calls: &facts.calls,
+		entry_point: &entry_point.name,
+		tracked,
+		cells: Slots(Vec::new()),
+		objects: Slots(Vec::new()),
+		object_sites: Vec::new(),
+		cell_sites: Vec::new(),
+		timelines: Vec::new(),
+		diagnostics: Vec::new(),
+		unknown_locals: unknown_locals.clone(),
+		locals: unknown_locals,
+		assumptions: Vec::new(),
+		returned_tests,
+		pending: Vec::new(),
+	};
+	for variable in 0..parameters {
+		checker.declare_parameter(variable);
+	}
+	for variable in parameters..checker.tracked.len() {
+		checker.declare(variable);
+	}
+	let declared = (checker.cells.clone(), checker.objects.clone());
+	checker.run(&cfg, &block_events);
+	if options.path_sensitive {
+		// the timelines stay the ones of the paths joined
+		let timelines = checker.timelines.clone();
+		(checker.cells, checker.objects) = declared;
+		match checker.run_paths(&cfg, &block_events) {
+			Some(diagnostics) => checker.diagnostics = diagnostics,
+			None => verbose!(
+				"{}: `{}` has more than {} paths, they are joined where they meet",
+				file,
+				entry_point.name,
+				options.path_budget
+			),
+		}
+		checker.timelines = timelines;
+	}
+
+	let mut report = Report::new();
+	report.diagnostics = checker.diagnostics;
+	report.timelines = checker.timelines;
+	return report;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::data_struct::{AbstractValue, Constant};
+
+	const GUARD: &str = "DataStruct IntVector {
+	Constructors {
+		IntVector_new()
+	}
+	Meta {
+		sorted: bool = true
+	}
+	Calls {
+		IntVector_push_back(&$self, ...) {
+			Does {
+				sorted = false
+			}
+		}
+		IntVector_sort(&$self) {
+			Does {
+				sorted = true
+			}
+		}
+		IntVector_binary_search(&$self, ...) {
+			Denies {
+				{
+					Test: sorted == false,
+					Message: \"vector is not sorted\"
+				}
+			}
+		}
+	}
+}";
+
+	fn check(code: &str) -> Vec<Diagnostic> {
+		let guards = DataStruct::parse_guards(GUARD).unwrap();
+		return check_source(&guards, &Options::default(), "main.c", code).diagnostics;
+	}
+
+	#[test]
+	fn test_deny_after_push_back() {
+		let diagnostics = check(
+			"int main() {
+	IntVector vec = IntVector_new();
+	IntVector_push_back(&vec, 3);
+	IntVector_binary_search(&vec, 3);
+}",
+		);
+		assert_eq!(diagnostics.len(), 1);
+		assert_eq!(diagnostics[0].rule_id, "IntVector.IntVector_binary_search.deny0");
+		assert_eq!(diagnostics[0].severity, Severity::Error);
+		assert_eq!(diagnostics[0].variable, Some("vec".to_string()));
+		assert_eq!(
+			diagnostics[0].meta,
+			vec![("sorted".to_string(), AbstractValue::OneOf(vec![Constant::Bool(false)]))]
+		);
+		assert_eq!((diagnostics[0].location.line, diagnostics[0].location.column), (4, 2));
+	}
+
+	#[test]
+	fn test_sort_restores_state() {
+		let diagnostics = check(
+			"int main() {
+	IntVector vec = IntVector_new();
+	IntVector_push_back(&vec, 3);
+	IntVector_sort(&vec);
+	IntVector_binary_search(&vec, 3);
+}",
+		);
+		assert!(diagnostics.is_empty());
+	}
+
+	#[test]
+	fn test_timeline() {
+		let guards = DataStruct::parse_guards(GUARD).unwrap();
+		let report = check_source(
+			&guards,
+			&Options::default(),
+			"main.c",
+			"int main() {
+	IntVector vec = IntVector_new();
+	IntVector_push_back(&vec, 3);
+	IntVector_sort(&vec);
+}",
+		);
+		assert_eq!(report.timelines.len(), 1);
+		let timeline = &report.timelines[0];
+		assert_eq!(timeline.variable, "vec");
+		assert_eq!(timeline.declaration.line, 2);
+		let sorted = |b| vec![("sorted".to_string(), AbstractValue::OneOf(vec![Constant::Bool(b)]))];
+		assert_eq!(timeline.initial, sorted(true));
+		let changes = timeline
+			.changes
+			.iter()
+			.map(|change| (change.call.as_str(), change.meta.clone()))
+			.collect::<Vec<_>>();
+		assert_eq!(
+			changes,
+			vec![
+				("IntVector_push_back(&vec, 3)", sorted(false)),
+				("IntVector_sort(&vec)", sorted(true))
+			]
+		);
+	}
+
+	#[test]
+	fn test_self_binding() {
+		let diagnostics = check(
+			"int main() {
+	IntVector vec = IntVector_new();
+	IntVector* p = IntVector_new();
+	IntVector arr[4];
+	arr[0] = IntVector_new();
+	IntVector_push_back(p, 3);
+	IntVector_binary_search(p, 3);
+	IntVector_push_back(&arr[1], 3);
+	IntVector_binary_search(&arr[2], 3);
+	IntVector_binary_search(&vec, 3);
+	IntVector_push_back(vec, 3);
+	IntVector_binary_search(&vec, 3);
+}",
+		);
+		let lines = diagnostics
+			.iter()
+			.map(|d| (d.variable.clone().unwrap(), d.location.line))
+			.collect::<Vec<_>>();
+		// `IntVector_push_back(vec, 3)` does not match `&$self`
+		assert_eq!(lines, vec![("p".to_string(), 7), ("arr".to_string(), 9)]);
+	}
+
+	#[test]
+	fn test_constructor_assignment() {
+		let diagnostics = check(
+			"int main() {
+	IntVector vec;
+	IntVector_push_back(&vec, 3);
+	vec = IntVector_new();
+	IntVector_binary_search(&vec, 3);
+	IntVector_push_back(&vec, 3);
+	IntVector_binary_search(&vec, 3);
+}",
+		);
+		let rules = diagnostics
+			.iter()
+			.map(|d| (d.rule_id.as_str(), d.location.line))
+			.collect::<Vec<_>>();
+		assert_eq!(
+			rules,
+			vec![
+				("IntVector.use-before-construct", 3),
+				("IntVector.IntVector_binary_search.deny0", 7)
+			]
+		);
+	}
+
+	#[test]
+	fn test_use_before_construct() {
+		let guards =
+			DataStruct::parse_guards(&GUARD.replace("IntVector_new()", "IntVector_new()\n}\nDestructors {\nIntVector_free(&$self)"))
+				.unwrap();
+		let diagnostics = check_source(
+			&guards,
+			&Options::default(),
+			"main.c",
+			"int main() {
+	IntVector vec;
+	IntVector* p = &vec;
+	IntVector_free(&vec);
+	IntVector_push_back(p, 3);
+}",
+		)
+		.diagnostics;
+		assert_eq!(diagnostics.len(), 2);
+		assert_eq!(diagnostics[0].rule_id, "IntVector.use-before-construct");
+		assert_eq!(diagnostics[0].message, "`vec` is used before being constructed");
+		// `p` points to `vec`
+		assert_eq!(diagnostics[1].rule_id, "IntVector.use-after-destroy");
+		assert_eq!(diagnostics[1].location.line, 5);
+		assert_eq!(diagnostics[0].location.line, 4);
+		assert_eq!(diagnostics[0].related[0].location.line, 2);
+		assert_eq!(diagnostics[0].related[0].message, "`vec` declared here without being constructed");
+	}
+
+	const DESTRUCTORS: &str = "IntVector_new()\n}\nDestructors {\nIntVector_free(&$self)";
+
+	fn check_destroyed(code: &str, options: &Options) -> Vec<(String, usize)> {
+		let guards = DataStruct::parse_guards(&GUARD.replace("IntVector_new()", DESTRUCTORS)).unwrap();
+		return check_source(&guards, options, "main.c", code)
+			.diagnostics
+			.into_iter()
+			.map(|d| (d.rule_id, d.location.line))
+			.collect();
+	}
+
+	#[test]
+	fn test_use_after_destroy() {
+		let code = "int main() {
+	IntVector vec = IntVector_new();
+	IntVector_free(&vec);
+	IntVector_push_back(&vec, 3);
+	IntVector_free(&vec);
+}";
+		assert_eq!(
+			check_destroyed(code, &Options::default()),
+			vec![
+				("IntVector.use-after-destroy".to_string(), 4),
+				("IntVector.double-destroy".to_string(), 5)
+			]
+		);
+		let options = Options {
+			use_after_destroy: false,
+			..Options::default()
+		};
+		assert!(check_destroyed(code, &options).is_empty());
+	}
+
+	#[test]
+	fn test_leaks() {
+		let code = "int main() {
+	IntVector vec = IntVector_new();
+	{
+		IntVector inner = IntVector_new();
+	}
+	IntVector_free(&vec);
+	IntVector other;
+	other = IntVector_new();
+	return 0;
+}";
+		assert_eq!(
+			check_destroyed(code, &Options::default()),
+			vec![("IntVector.leak".to_string(), 5), ("IntVector.leak".to_string(), 9)]
+		);
+		let options = Options {
+			leaks: false,
+			..Options::default()
+		};
+		assert!(check_destroyed(code, &options).is_empty());
+	}
+
+	#[test]
+	fn test_leak_on_overwrite() {
+		let guards = DataStruct::parse_guards(&GUARD.replace("IntVector_new()", DESTRUCTORS)).unwrap();
+		let code = "int main() {
+	IntVector vec = IntVector_new();
+	vec = IntVector_new();
+	IntVector_free(&vec);
+	vec = IntVector_new();
+	IntVector_free(&vec);
+}";
+		let diagnostics = check_source(&guards, &Options::default(), "main.c", code).diagnostics;
+		assert_eq!(diagnostics.len(), 1);
+		assert_eq!(diagnostics[0].rule_id, "IntVector.leak");
+		assert_eq!(diagnostics[0].location.line, 3);
+		assert_eq!(
+			diagnostics[0].message,
+			"`vec` is given a new object before its previous one is destroyed"
+		);
+		assert_eq!(diagnostics[0].related[0].location.line, 2);
+		assert_eq!(diagnostics[0].related[0].message, "`vec` constructed here");
+	}
+
+	#[test]
+	fn test_noreturn_ends_paths() {
+		let code = "_Noreturn void fatal(const char* message);
+int main(int argc) {
+	IntVector vec = IntVector_new();
+	if (argc > 3) {
+		exit(1);
+		return 3;
+	}
+	if (argc > 2) {
+		fatal(\"too many\");
+		return 2;
+	}
+	if (argc > 1) {
+		panic(\"usage\");
+		return 1;
+	}
+	IntVector_free(&vec);
+	return 0;
+}";
+		assert_eq!(check_destroyed(code, &Options::default()), vec![("IntVector.leak".to_string(), 14)]);
+		for path_sensitive in [false, true] {
+			let options = Options {
+				path_sensitive,
+				noreturn: vec!["panic".to_string()],
+				..Options::default()
+			};
+			assert!(check_destroyed(code, &options).is_empty());
+		}
+	}
+
+	#[test]
+	fn test_function_summaries() {
+		let guards = DataStruct::parse_guards(&GUARD.replace("IntVector_new()", DESTRUCTORS)).unwrap();
+		let code = "void add(IntVector* v, int n) {
+	if (n > 0) {
+		IntVector_push_back(v, n);
+	}
+}
+int lookup(IntVector* v, int x) {
+	return IntVector_binary_search(v, x);
+}
+void release(IntVector* v) {
+	IntVector_free(v);
+}
+int main(int argc) {
+	IntVector vec = IntVector_new();
+	add(&vec, argc);
+	lookup(&vec, 1);
+	IntVector_sort(&vec);
+	lookup(&vec, 2);
+	release(&vec);
+	IntVector_sort(&vec);
+}";
+		let diagnostics = check_source(&guards, &Options::default(), "main.c", code).diagnostics;
+		let messages = diagnostics
+			.iter()
+			.map(|d| (d.rule_id.as_str(), d.message.as_str(), d.location.line))
+			.collect::<Vec<_>>();
+		// `release` destroys the vector, which does not leak
+		assert_eq!(
+			messages,
+			vec![
+				("IntVector.lookup.deny0", "vector is not sorted (in `lookup`) on some paths", 15),
+				("IntVector.use-after-destroy", "`vec` is used after being destroyed", 19)
+			]
+		);
+	}
+
+	#[test]
+	fn test_wrapped_double_destroy() {
+		let guards = DataStruct::parse_guards(&GUARD.replace("IntVector_new()", DESTRUCTORS)).unwrap();
+		let code = "void release(IntVector* v) {
+	IntVector_free(v);
+}
+int main() {
+	IntVector vec = IntVector_new();
+	release(&vec);
+	release(&vec);
+	IntVector vec2 = IntVector_new();
+	IntVector_free(&vec2);
+	IntVector_free(&vec2);
+}";
+		let diagnostics = check_source(&guards, &Options::default(), "main.c", code).diagnostics;
+		let messages = diagnostics
+			.iter()
+			.map(|d| (d.rule_id.as_str(), d.location.line))
+			.collect::<Vec<_>>();
+		// the same as calling the destructor directly
+		assert_eq!(messages, vec![("IntVector.double-destroy", 7), ("IntVector.double-destroy", 10)]);
+	}
+
+	#[test]
+	fn test_entry_points() {
+		let guards = DataStruct::parse_guards(GUARD).unwrap();
+		let code = "void test_unsorted(void) {
+	IntVector vec = IntVector_new();
+	IntVector_push_back(&vec, 3);
+	IntVector_binary_search(&vec, 3);
+}
+void search(IntVector* v, IntVector all[]) {
+	IntVector_binary_search(v, 3);
+	IntVector_sort(v);
+	IntVector_binary_search(v, 3);
+	IntVector_binary_search(&all[0], 3);
+}
+void test_sorted(void) {
+	IntVector vec = IntVector_new();
+	IntVector_sort(&vec);
+	search(&vec, &vec);
+}";
+		let check = |entry_points: &[&str]| {
+			let options = Options {
+				entry_points: entry_points.iter().map(|pattern| pattern.to_string()).collect(),
+				..Options::default()
+			};
+			let report = check_source(&guards, &options, "test.c", code);
+			return report
+				.diagnostics
+				.iter()
+				.map(|d| (d.message.clone(), d.location.line))
+				.collect::<Vec<_>>();
+		};
+		// no `main`, nothing to check
+		assert!(check(&["main"]).is_empty());
+		assert_eq!(check(&["test_*"]), vec![("vector is not sorted".to_string(), 4)]);
+		// nothing is known of the vectors given to `search`
+		assert_eq!(
+			check(&["*"]),
+			vec![
+				("vector is not sorted".to_string(), 4),
+				("vector is not sorted on some paths".to_string(), 7),
+				("vector is not sorted on some paths".to_string(), 10)
+			]
+		);
+	}
+
+	#[test]
+	fn test_escape() {
+		let code = "int main() {
+	IntVector vec = IntVector_new();
+	my_lib_helper(&vec);
+	IntVector_binary_search(&vec, 3);
+	IntVector_sort(&vec);
+	printf(\"%d\", 3);
+	print_vector(vec);
+	IntVector_binary_search(&vec, 3);
+}";
+		let check = |guard: &str, escape: Escape| {
+			let options = Options {
+				escape,
+				..Options::default()
+			};
+			let report = check_source(&DataStruct::parse_guards(guard).unwrap(), &options, "main.c", code);
+			return report
+				.diagnostics
+				.iter()
+				.map(|d| (d.rule_id.clone(), d.message.clone(), d.location.line))
+				.collect::<Vec<_>>();
+		};
+		// a copy shares the object it is made of
+		let unsorted = |line| {
+			(
+				"IntVector.IntVector_binary_search.deny0".to_string(),
+				"vector is not sorted on some paths".to_string(),
+				line,
+			)
+		};
+		assert_eq!(check(GUARD, Escape::Havoc), vec![unsorted(4), unsorted(8)]);
+		assert!(check(GUARD, Escape::Keep).is_empty());
+		let escapes = |line, function| {
+			let message = format!("`vec` escapes into unknown function `{}`", function);
+			return ("IntVector.escape".to_string(), message, line);
+		};
+		assert_eq!(
+			check(GUARD, Escape::Warn),
+			vec![escapes(3, "my_lib_helper"), escapes(7, "print_vector")]
+		);
+		// the guard has the last word
+		let keep = GUARD.replace("Meta {", "Handle {\n\t\tEscapes: keep\n\t}\n\tMeta {");
+		assert!(check(&keep, Escape::Warn).is_empty());
+	}
+
+	#[test]
+	fn test_aliases_share_state() {
+		let diagnostics = check(
+			"int main() {
+	IntVector vec = IntVector_new();
+	IntVector* p = &vec;
+	IntVector** pp = &p;
+	IntVector_push_back(&vec, 3);
+	IntVector_sort(p);
+	IntVector_binary_search(&vec, 3);
+	IntVector copy = vec;
+	IntVector_push_back(*pp, 3);
+	IntVector_binary_search(&copy, 3);
+}",
+		);
+		assert_eq!(diagnostics.len(), 1);
+		assert_eq!(diagnostics[0].location.line, 10);
+		assert_eq!(diagnostics[0].variable, Some("copy".to_string()));
+	}
+
+	#[test]
+	fn test_aliases_and_leaks() {
+		let code = "int main() {
+	IntVector vec = IntVector_new();
+	IntVector* p = &vec;
+	IntVector copy = vec;
+	IntVector_free(p);
+	IntVector_free(&copy);
+	IntVector* q = IntVector_new();
+	q = p;
+}";
+		assert_eq!(
+			check_destroyed(code, &Options::default()),
+			vec![("IntVector.double-destroy".to_string(), 6), ("IntVector.leak".to_string(), 8)]
+		);
+	}
+
+	#[test]
+	fn test_no_copy() {
+		let guards = DataStruct::parse_guards(&GUARD.replace("DataStruct IntVector {", "DataStruct IntVector NoCopy {")).unwrap();
+		let code = "int main() {
+	IntVector vec = IntVector_new();
+	IntVector* p = &vec;
+	IntVector copy = *p;
+	copy = vec;
+}";
+		let diagnostics = check_source(&guards, &Options::default(), "main.c", code).diagnostics;
+		let messages = diagnostics
+			.iter()
+			.map(|d| (d.rule_id.as_str(), d.message.as_str(), d.location.line))
+			.collect::<Vec<_>>();
+		assert_eq!(
+			messages,
+			vec![
+				("IntVector.copy", "`copy` is a copy of `p`, IntVector must not be copied", 4),
+				("IntVector.copy", "`copy` is a copy of `vec`, IntVector must not be copied", 5)
+			]
+		);
+	}
+
+	const GRAPH: &str = "DataStruct Graph {
+	Handle {
+		Kind: pointer
+	}
+	Constructors {
+		Graph_load(...)
+	}
+	Destructors {
+		Graph_free($self)
+	}
+	Meta {
+		indexed: bool = false
+	}
+	Calls {
+		Graph_index($self) {
+			Does {
+				indexed = true
+			}
+		}
+		Graph_query($self) {
+			Denies {
+				{
+					Test: indexed == false
+				}
+			}
+		}
+	}
+}";
+
+	fn check_graph(guard: &str, code: &str) -> Vec<(String, usize)> {
+		let guards = DataStruct::parse_guards(guard).unwrap();
+		return check_source(&guards, &Options::default(), "main.c", code)
+			.diagnostics
+			.into_iter()
+			.map(|d| (d.rule_id, d.location.line))
+			.collect();
+	}
+
+	#[test]
+	fn test_pointer_handle() {
+		let code = "int main() {
+	Graph* g = Graph_load(\"graph.txt\");
+	if (g == NULL) {
+		return 1;
+	}
+	Graph** gp = &g;
+	Graph_query(*gp);
+	Graph_index(g);
+	Graph_query(g);
+	Graph_free(g);
+	return 0;
+}";
+		assert_eq!(
+			check_graph(GRAPH, code),
+			vec![("Graph.leak".to_string(), 4), ("Graph.Graph_query.deny0".to_string(), 7)]
+		);
+	}
+
+	#[test]
+	fn test_null_handles() {
+		let code = "int main() {
+	Graph* g = Graph_load(\"graph.txt\");
+	Graph_index(g);
+	Graph_query(g);
+	Graph_free(g);
+	g = NULL;
+	Graph_query(g);
+	Graph_free(g);
+}";
+		assert_eq!(
+			check_graph(GRAPH, code),
+			vec![("Graph.unchecked-null".to_string(), 3), ("Graph.null".to_string(), 7)]
+		);
+		let not_nullable = GRAPH.replace("Kind: pointer", "Kind: pointer\nNullable: false");
+		assert_eq!(check_graph(&not_nullable, code), vec![("Graph.null".to_string(), 7)]);
+	}
+
+	#[test]
+	fn test_opaque_and_integer_handles() {
+		let opaque = GRAPH.replace("Kind: pointer", "Kind: pointer\nType: GraphHandle");
+		let code = "int main() {
+	GraphHandle g = Graph_load(\"graph.txt\");
+	if (!g) {
+		return 1;
+	}
+	Graph_query(g);
+	Graph_free(g);
+	return 0;
+}";
+		assert_eq!(
+			check_graph(&opaque, code),
+			vec![("Graph.leak".to_string(), 4), ("Graph.Graph_query.deny0".to_string(), 6)]
+		);
+
+		let file = GRAPH.replace("Kind: pointer", "Kind: integer").replace("Graph_", "File_");
+		let code = "int main() {
+	int fd = File_load(\"graph.txt\");
+	int other = 0;
+	File_query(fd);
+	File_free(fd);
+	File_query(fd);
+	File_query(other);
+}";
+		// an integer handle is never NULL, `0` is a valid file descriptor
+		assert_eq!(
+			check_graph(&file, code),
+			vec![
+				("Graph.File_query.deny0".to_string(), 4),
+				("Graph.use-after-destroy".to_string(), 6),
+				("Graph.File_query.deny0".to_string(), 6),
+				("Graph.File_query.deny0".to_string(), 7)
+			]
+		);
+	}
+
+	#[test]
+	fn test_branches() {
+		let diagnostics = check(
+			"int main(int argc) {
+	IntVector vec = IntVector_new();
+	if (argc > 1) {
+		IntVector_push_back(&vec, 3);
+	}
+	IntVector_binary_search(&vec, 3);
+	if (argc > 2) {
+		IntVector_push_back(&vec, 3);
+	}
+	else {
+		IntVector_push_back(&vec, 4);
+	}
+	IntVector_binary_search(&vec, 3);
+	goto end;
+	IntVector_push_back(&vec, 3);
+end:
+	IntVector_sort(&vec);
+	IntVector_binary_search(&vec, 3);
+}",
+		);
+		// the first search may or may not be on a sorted vector, the second is not on any path
+		let lines = diagnostics
+			.iter()
+			.map(|d| (d.location.line, d.severity, d.message.as_str()))
+			.collect::<Vec<_>>();
+		assert_eq!(
+			lines,
+			vec![
+				(6, Severity::Warning, "vector is not sorted on some paths"),
+				(13, Severity::Error, "vector is not sorted")
+			]
+		);
+		assert_eq!(
+			diagnostics[0].meta,
+			vec![(
+				"sorted".to_string(),
+				AbstractValue::OneOf(vec![Constant::Bool(true), Constant::Bool(false)])
+			)]
+		);
+	}
+
+	#[test]
+	fn test_early_return_and_loops() {
+		let code = "int main(int argc) {
+	IntVector vec = IntVector_new();
+	if (argc > 1) {
+		IntVector_free(&vec);
+		return 1;
+	}
+	for (int i = 0; i < argc; i++) {
+		IntVector_push_back(&vec, i);
+	}
+	while (argc--) {
+		IntVector_push_back(&vec, 3);
+		IntVector_sort(&vec);
+		IntVector_binary_search(&vec, 3);
+	}
+	IntVector_binary_search(&vec, 3);
+	IntVector_free(&vec);
+	return 0;
+}";
+		// `vec` is only destroyed on the path that returns, and may not be sorted after the `for` loop
+		assert_eq!(
+			check_destroyed(code, &Options::default()),
+			vec![("IntVector.IntVector_binary_search.deny0".to_string(), 15)]
+		);
+	}
+
+	#[test]
+	fn test_may_violate() {
+		let code = "int main(int argc) {
+	IntVector vec = IntVector_new();
+	if (argc > 1) {
+		IntVector_free(&vec);
+	}
+	IntVector_sort(&vec);
+	IntVector_free(&vec);
+}";
+		let guards = DataStruct::parse_guards(&GUARD.replace("IntVector_new()", DESTRUCTORS)).unwrap();
+		let diagnostics = check_source(&guards, &Options::default(), "main.c", code).diagnostics;
+		let messages = diagnostics
+			.iter()
+			.map(|d| (d.severity, d.message.as_str(), d.location.line))
+			.collect::<Vec<_>>();
+		assert_eq!(
+			messages,
+			vec![
+				(Severity::Warning, "`vec` is used after being destroyed on some paths", 6),
+				(Severity::Warning, "`vec` is destroyed twice on some paths", 7)
+			]
+		);
+
+		let options = Options {
+			may_severity: None,
+			..Options::default()
+		};
+		assert!(check_source(&guards, &options, "main.c", code).diagnostics.is_empty());
+	}
+
+	#[test]
+	fn test_path_sensitive() {
+		let code = "int main(int argc) {
+	IntVector vec = IntVector_new();
+	IntVector_push_back(&vec, 1);
+	bool already_sorted = argc > 2;
+	if (!already_sorted) {
+		IntVector_sort(&vec);
+	}
+	if (!already_sorted) {
+		IntVector_binary_search(&vec, 1);
+	}
+	int tries = 0;
+	while (tries < 3 && !already_sorted) {
+		tries += 1;
+	}
+	if (already_sorted && tries == 0) {
+		IntVector_binary_search(&vec, 2);
+	}
+}";
+		let guards = DataStruct::parse_guards(GUARD).unwrap();
+		let lines = |options: &Options| {
+			let diagnostics = check_source(&guards, options, "main.c", code).diagnostics;
+			return diagnostics
+				.into_iter()
+				.map(|d| (d.location.line, d.severity, d.path))
+				.collect::<Vec<_>>();
+		};
+		assert_eq!(
+			lines(&Options::default()),
+			vec![(9, Severity::Warning, Vec::new()), (16, Severity::Warning, Vec::new())]
+		);
+		let options = Options {
+			path_sensitive: true,
+			..Options::default()
+		};
+		// the vector is sorted whenever it is searched the first time, and never when it is the second time
+		assert_eq!(lines(&options), vec![(16, Severity::Error, vec![2, 3, 4, 5, 8, 11, 12, 15, 16])]);
+		// past the budget, the paths are joined
+		let options = Options { path_budget: 2, ..options };
+		assert_eq!(lines(&options), lines(&Options::default()));
+	}
+
+	#[test]
+	fn test_return_outcomes() {
+		let guards = DataStruct::parse_guards(
+			"DataStruct Graph {
+	Meta {
+		indexed: bool = false
+	}
+	Calls {
+		Graph_build_index(&$self) {
+			Returns {
+				== 0: Does {
+					indexed = true
+				}
+				else: Does {
+				}
+			}
+		}
+		Graph_nodes_at(&$self, ...) {
+			Denies {
+				{
+					Test: indexed == false,
+					Message: \"the index is not built\"
+				}
+			}
+		}
+	}
+}",
+		)
+		.unwrap();
+		let code = "int main() {
+	Graph g;
+	if (Graph_build_index(&g) != 0) {
+		Graph_nodes_at(&g, 1);
+		return 1;
+	}
+	Graph_nodes_at(&g, 2);
+	Graph h;
+	Graph_build_index(&h);
+	Graph_nodes_at(&h, 3);
+	Graph k;
+	while (!(Graph_build_index(&k) == 0)) {
+	}
+	Graph_nodes_at(&k, 4);
+}";
+		for path_sensitive in [false, true] {
+			let options = Options {
+				path_sensitive,
+				..Options::default()
+			};
+			let diagnostics = check_source(&guards, &options, "main.c", code).diagnostics;
+			let lines = diagnostics.iter().map(|d| (d.location.line, d.severity)).collect::<Vec<_>>();
+			// the index is only built when it returns 0, whatever it returns when it is not compared
+			assert_eq!(lines, vec![(4, Severity::Error), (10, Severity::Warning)]);
+		}
+	}
+
+	#[test]
+	fn test_loop_widening() {
+		let guards = DataStruct::parse_guards(
+			"DataStruct Stack {
+	Meta {
+		size: number = 0
+	}
+	Calls {
+		Stack_push(&$self, ...) {
+			Does {
+				size = size + 1
+			}
+		}
+		Stack_pop(&$self) {
+			Denies {
+				{
+					Test: size == 0,
+					Message: \"stack is empty\"
+				}
+			}
+			Does {
+				size = size - 1
+			}
+		}
+	}
+}",
+		)
+		.unwrap();
+		let code = "int main(int argc) {
+	Stack s = Stack_make();
+	while (argc--) {
+		Stack_push(&s, 1);
+		Stack_pop(&s);
+	}
+	Stack_pop(&s);
+	Stack_push(&s, 1);
+	Stack_push(&s, 2);
+	for (int i = 0; i < argc; i++) {
+		Stack_push(&s, i);
+	}
+	Stack_pop(&s);
+	Stack_pop(&s);
+}";
+		let diagnostics = check_source(&guards, &Options::default(), "main.c", code).diagnostics;
+		let lines = diagnostics.iter().map(|d| (d.location.line, d.severity)).collect::<Vec<_>>();
+		// the size is the same at each iteration of the first loop, the second one only grows it from 1
+		assert_eq!(lines, vec![(7, Severity::Error), (14, Severity::Warning)]);
+	}
+}
@@ -1,6 +1,11 @@
+use std::fmt;
+
 use either::Either;
 
-use crate::{ast::ProgramVariable, parser::Bracketed};
+use crate::{
+	debug,
+	parser::{fill_blanks, parse_bracketed, Bracketed},
+};
 
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum VariableType {
@@ -23,31 +28,36 @@ pub enum Identifier {
 }
 
 impl Identifier {
-	fn var(name: &str) -> Identifier {
+	pub fn var(name: &str) -> Identifier {
 		Identifier::Variable { name: name.to_string() }
 	}
 
-	fn const_bool(b: bool) -> Identifier {
+	pub fn const_bool(b: bool) -> Identifier {
 		Identifier::Constant(Constant::Bool(b))
 	}
 
-	fn const_num(n: i32) -> Identifier {
+	pub fn const_num(n: i32) -> Identifier {
 		Identifier::Constant(Constant::Number(n))
 	}
 
-	fn const_str(s: &str) -> Identifier {
+	pub fn const_str(s: &str) -> Identifier {
 		Identifier::Constant(Constant::String(s.to_string()))
 	}
 }
 
 #[derive(Debug, PartialEq, Copy, Clone)]
-enum Operator {
+pub enum Operator {
 	Addition,
 	Substraction,
 	Multiplication,
 	Division,
 	UnaryMinus,
 	Equals,
+	NotEquals,
+	Lower,
+	LowerOrEqual,
+	Greater,
+	GreaterOrEqual,
 	Not,
 	And,
 	Or,
@@ -66,7 +76,8 @@ impl Operator {
 		match self {
 			Addition | Substraction | Multiplication | Division => ArityAndTypes::Binary(Number, Number),
 			UnaryMinus => ArityAndTypes::Unary(Number),
-			Equals => ArityAndTypes::Binary(Number, Number),
+			Equals | NotEquals => ArityAndTypes::Binary(Number, Number),
+			Lower | LowerOrEqual | Greater | GreaterOrEqual => ArityAndTypes::Binary(Number, Number),
 			Not => ArityAndTypes::Unary(Bool),
 			And | Or => ArityAndTypes::Binary(Bool, Bool),
 		}
@@ -79,13 +90,58 @@ impl Operator {
 			UnaryMinus => 300,
 			Not => 300,
 			Multiplication | Division => 200,
-			Equals => 50,
+			Lower | LowerOrEqual | Greater | GreaterOrEqual => 60,
+			Equals | NotEquals => 50,
 			And => 25,
 			Or => 20,
 		}
 	}
 
-	fn from_string(op: &str) -> Result<Operator, String> {
+	/// `!=`, `<`, `<=`, `>` and `>=`, `==` compares more than numbers
+	pub fn is_comparison(&self) -> bool {
+		use Operator::*;
+		return matches!(self, NotEquals | Lower | LowerOrEqual | Greater | GreaterOrEqual);
+	}
+
+	fn compare(&self, n1: &i32, n2: &i32) -> bool {
+		use Operator::*;
+		match self {
+			NotEquals => return n1 != n2,
+			Lower => return n1 < n2,
+			LowerOrEqual => return n1 <= n2,
+			Greater => return n1 > n2,
+			GreaterOrEqual => return n1 >= n2,
+			_ => panic!("{:?} is not a comparison", self),
+		}
+	}
+
+	/// The comparison holding when this one does not, `<` for `>=`
+	pub fn negation(&self) -> Operator {
+		use Operator::*;
+		match self {
+			Equals => return NotEquals,
+			NotEquals => return Equals,
+			Lower => return GreaterOrEqual,
+			LowerOrEqual => return Greater,
+			Greater => return LowerOrEqual,
+			GreaterOrEqual => return Lower,
+			_ => panic!("{:?} is not a comparison", self),
+		}
+	}
+
+	/// The comparison holding with its operands swapped, `>` for `<`
+	pub fn mirror(&self) -> Operator {
+		use Operator::*;
+		match self {
+			Lower => return Greater,
+			LowerOrEqual => return GreaterOrEqual,
+			Greater => return Lower,
+			GreaterOrEqual => return LowerOrEqual,
+			op => return *op,
+		}
+	}
+
+	pub fn from_string(op: &str) -> Result<Operator, String> {
 		use Operator::*;
 		match op {
 			"+" => Ok(Addition),
@@ -93,12 +149,37 @@ impl Operator {
 			"*" => Ok(Multiplication),
 			"/" => Ok(Division),
 			"==" => Ok(Equals),
+			"!=" => Ok(NotEquals),
+			"<" => Ok(Lower),
+			"<=" => Ok(LowerOrEqual),
+			">" => Ok(Greater),
+			">=" => Ok(GreaterOrEqual),
 			"!" => Ok(Not),
 			"&&" => Ok(And),
 			"||" => Ok(Or),
 			_ => Err(format!("Unknown operator: {}", op)),
 		}
 	}
+
+	/// The operator as written in a guard, the reverse of [`Operator::from_string`]
+	pub fn as_str(&self) -> &'static str {
+		use Operator::*;
+		match self {
+			Addition => "+",
+			Substraction | UnaryMinus => "-",
+			Multiplication => "*",
+			Division => "/",
+			Equals => "==",
+			NotEquals => "!=",
+			Lower => "<",
+			LowerOrEqual => "<=",
+			Greater => ">",
+			GreaterOrEqual => ">=",
+			Not => "!",
+			And => "&&",
+			Or => "||",
+		}
+	}
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -110,27 +191,25 @@ pub enum Token {
 
 impl Token {
 	fn is_operand(&self) -> bool {
-		match self {
-			Token::Operand(_) => true,
-			_ => false,
-		}
+		matches!(self, Token::Operand(_))
 	}
 
-	fn parse(expr: &str) -> Vec<Token> {
+	fn parse(expr: &str) -> Result<Vec<Token>, String> {
 		// remove all whitespaces
 		let expr = expr.replace(" ", "");
 		let mut tokens = Vec::new();
 		let mut i = 0;
 		while i < expr.len() {
 			let c = expr.chars().nth(i).unwrap();
-			if c.is_digit(10) {
+			if c.is_ascii_digit() {
 				let mut number = c.to_string();
 				i += 1;
-				while i < expr.len() && expr.chars().nth(i).unwrap().is_digit(10) {
+				while i < expr.len() && expr.chars().nth(i).unwrap().is_ascii_digit() {
 					number.push(expr.chars().nth(i).unwrap());
 					i += 1;
 				}
-				tokens.push(Token::Operand(Identifier::Constant(Constant::Number(number.parse().unwrap()))));
+				let number = number.parse().map_err(|_| format!("Number too large: {}", number))?;
+				tokens.push(Token::Operand(Identifier::Constant(Constant::Number(number))));
 			}
 			else if c == '"' {
 				let mut string = String::new();
@@ -156,32 +235,21 @@ impl Token {
 				i += 1;
 			}
 			else {
-				let mut op = c.to_string();
-				let begin = i;
-				let mut end = i + 1;
-				while end < expr.len() {
-					match Operator::from_string(expr[begin..end].as_ref()) {
-						Ok(_) => {
-							op = expr[begin..end].to_string();
-							break;
-						}
-						Err(_) => {
-							end += 1;
-						}
-					}
-				}
-				match Operator::from_string(&op) {
-					Ok(op) => tokens.push(Token::Operation(op)),
-					Err(e) => panic!("{}", e),
-				}
-				i = end;
+				// the longest operator, `<=` rather than `<`
+				let length = match expr.get(i..i + 2).map(Operator::from_string) {
+					Some(Ok(_)) => 2,
+					_ => 1,
+				};
+				let op = expr.get(i..i + length).ok_or(format!("Unexpected character in {:?}", expr))?;
+				tokens.push(Token::Operation(Operator::from_string(op)?));
+				i += length;
 			}
 		}
-		return tokens;
+		return Ok(tokens);
 	}
 
-	fn from_string(expr: &str) -> Vec<Token> {
-		let mut tokens = Token::parse(expr);
+	fn from_string(expr: &str) -> Result<Vec<Token>, String> {
+		let mut tokens = Token::parse(expr)?;
 		let mut i = 0;
 		while i < tokens.len() {
 			if let Token::Operation(Operator::Substraction) = tokens[i] {
@@ -191,12 +259,48 @@ impl Token {
 			}
 			i += 1;
 		}
-		return tokens;
+		return Ok(tokens);
 	}
 }
 
 pub type EvaluableExpr = Vec<Token>;
 
+/// Parses an expression of a guard, e.g. `sorted == false && size > 0`
+pub fn parse_expression(expr: &str) -> Result<EvaluableExpr, String> {
+	let tokens = Token::from_string(expr)?;
+	let mut open: usize = 0;
+	for token in &tokens {
+		match token {
+			Token::Parenthesis('(') => open += 1,
+			Token::Parenthesis(_) => open = open.checked_sub(1).ok_or(format!("Unbalanced parentheses in {:?}", expr))?,
+			_ => {}
+		}
+	}
+	if open != 0 {
+		return Err(format!("Unbalanced parentheses in {:?}", expr));
+	}
+	let rpn = expr_tokens_to_rpn(tokens);
+	// every operator has its operands and the expression gives a single value
+	let mut depth: usize = 0;
+	for token in &rpn {
+		match token {
+			Token::Operand(_) => depth += 1,
+			Token::Operation(op) => {
+				let arity = match op.arity_and_types() {
+					ArityAndTypes::Unary(_) => 1,
+					ArityAndTypes::Binary(_, _) => 2,
+				};
+				depth = depth.checked_sub(arity).ok_or(format!("Missing an operand in {:?}", expr))? + 1;
+			}
+			Token::Parenthesis(_) => panic!("Invalid token {:?}", token),
+		}
+	}
+	if depth != 1 {
+		return Err(format!("Invalid expression: {:?}", expr));
+	}
+	return Ok(rpn);
+}
+
 fn expr_tokens_to_rpn(tokens: Vec<Token>) -> EvaluableExpr {
 	let mut output = Vec::new();
 	let mut stack = Vec::new();
@@ -211,7 +315,7 @@ fn expr_tokens_to_rpn(tokens: Vec<Token>) -> EvaluableExpr {
 				},
 			},
 			Token::Operation(ref op) => {
-				while let Some(&ref top) = stack.last() {
+				while let Some(top) = stack.last() {
 					if let Token::Operation(top_op) = top {
 						if top_op.priority() >= op.priority() {
 							output.push(stack.pop().unwrap());
@@ -246,85 +350,27 @@ fn expr_tokens_to_rpn(tokens: Vec<Token>) -> EvaluableExpr {
 	return output;
 }
 
-fn evaluate_rpn(tokens: EvaluableExpr) -> Identifier {
-	let mut stack = Vec::new();
-	for token in tokens {
-		match token {
-			Token::Operand(operand) => stack.push(operand),
-			Token::Operation(op) => {
-				let arity_and_types = op.arity_and_types();
-				match arity_and_types {
-					ArityAndTypes::Unary(_) => {
-						let operand = stack.pop().unwrap();
-						match (&op, &operand) {
-							(Operator::UnaryMinus, Identifier::Constant(Constant::Number(n))) => {
-								stack.push(Identifier::Constant(Constant::Number(-n)))
-							}
-							(Operator::Not, Identifier::Constant(Constant::Bool(b))) => {
-								stack.push(Identifier::Constant(Constant::Bool(!b)))
-							}
-							_ => panic!("Invalid unary operation {:?} {:?}", op, operand),
-						}
-					}
-					ArityAndTypes::Binary(_, _) => {
-						let operand2 = stack.pop().unwrap();
-						let operand1 = stack.pop().unwrap();
-						match (&op, &operand1, &operand2) {
-							(
-								Operator::Addition,
-								Identifier::Constant(Constant::Number(n1)),
-								Identifier::Constant(Constant::Number(n2)),
-							) => stack.push(Identifier::Constant(Constant::Number(n1 + n2))),
-							(
-								Operator::Substraction,
-								Identifier::Constant(Constant::Number(n1)),
-								Identifier::Constant(Constant::Number(n2)),
-							) => stack.push(Identifier::Constant(Constant::Number(n1 - n2))),
-							(
-								Operator::Multiplication,
-								Identifier::Constant(Constant::Number(n1)),
-								Identifier::Constant(Constant::Number(n2)),
-							) => stack.push(Identifier::Constant(Constant::Number(n1 * n2))),
-							(
-								Operator::Division,
-								Identifier::Constant(Constant::Number(n1)),
-								Identifier::Constant(Constant::Number(n2)),
-							) => stack.push(Identifier::Constant(Constant::Number(n1 / n2))),
-							(Operator::Equals, Identifier::Constant(Constant::Number(n1)), Identifier::Constant(Constant::Number(n2))) => {
-								stack.push(Identifier::Constant(Constant::Bool(n1 == n2)))
-							}
-							(Operator::And, Identifier::Constant(Constant::Bool(b1)), Identifier::Constant(Constant::Bool(b2))) => {
-								stack.push(Identifier::Constant(Constant::Bool(*b1 && *b2)))
-							}
-							(Operator::Or, Identifier::Constant(Constant::Bool(b1)), Identifier::Constant(Constant::Bool(b2))) => {
-								stack.push(Identifier::Constant(Constant::Bool(*b1 || *b2)))
-							}
-
-							_ => panic!("Invalid binary operation {:?} {:?} {:?}", op, operand1, operand2),
-						}
-					}
-				}
-			}
-			_ => panic!("Invalid token {:?}", token),
-		}
-	}
-	return stack.pop().unwrap();
-}
-
+#[derive(Debug, PartialEq, Clone)]
 pub struct MetaData {
 	pub name: String,
 	pub data: Constant,
 }
 
 impl MetaData {
-	pub fn from_string(s: &str) -> MetaData {
+	pub fn from_string(s: &str) -> Result<MetaData, String> {
 		// name: type = value
-		let parts: Vec<&str> = s.split(":").collect();
-		let name = parts[0].trim().to_string();
-		let parts: Vec<&str> = parts[1].split("=").collect();
-		let data_type = parts[0].trim();
-		let data = parts[1].trim();
-		let data = if data.starts_with('"') {
+		let Some((name, rest)) = s.split_once(':')
+		else {
+			return Err(format!("Expected `name: type = value` in Meta, found: {:?}", s));
+		};
+		let Some((data_type, data)) = rest.split_once('=')
+		else {
+			return Err(format!("Expected `name: type = value` in Meta, found: {:?}", s));
+		};
+		let name = name.trim().to_string();
+		let data_type = data_type.trim();
+		let data = data.trim();
+		let data = if data.len() >= 2 && data.starts_with('"') && data.ends_with('"') {
 			Constant::String(data[1..data.len() - 1].to_string())
 		}
 		else if data == "true" {
@@ -334,18 +380,18 @@ impl MetaData {
 			Constant::Bool(false)
 		}
 		else {
-			Constant::Number(data.parse().unwrap())
+			Constant::Number(data.parse().map_err(|_| format!("Invalid value for {}: {:?}", name, data))?)
 		};
-		if data_type == "bool" && !matches!(data, Constant::Bool(_)) {
-			panic!("Invalid data type for {}: {:?}", name, data);
-		}
-		if data_type == "number" && !matches!(data, Constant::Number(_)) {
-			panic!("Invalid data type for {}: {:?}", name, data);
-		}
-		if data_type == "string" && !matches!(data, Constant::String(_)) {
-			panic!("Invalid data type for {}: {:?}", name, data);
+		let valid = match data_type {
+			"bool" => matches!(data, Constant::Bool(_)),
+			"number" => matches!(data, Constant::Number(_)),
+			"string" => matches!(data, Constant::String(_)),
+			_ => return Err(format!("Unknown type for {}: {:?}", name, data_type)),
+		};
+		if !valid {
+			return Err(format!("Invalid data type for {}: {:?}", name, data));
 		}
-		return MetaData { name, data };
+		return Ok(MetaData { name, data });
 	}
 }
 
@@ -356,21 +402,33 @@ pub struct Assignement {
 }
 
 impl Assignement {
-	pub fn from_string(s: &str) -> Assignement {
-		let parts: Vec<&str> = s.split("=").collect();
-		let name = parts[0].trim().to_string();
-		let value = parts[1].trim();
-		let tokens = Token::from_string(value);
-		let rpn = expr_tokens_to_rpn(tokens);
-		return Assignement { name, value: rpn };
+	pub fn from_string(s: &str) -> Result<Assignement, String> {
+		let Some((name, value)) = s.split_once('=')
+		else {
+			return Err(format!("Expected `name = value` in Does, found: {:?}", s));
+		};
+		let name = name.trim().to_string();
+		let value = parse_expression(value.trim())?;
+		return Ok(Assignement { name, value });
+	}
+
+	/// As written in a guard, e.g. `sorted = !reversed`
+	pub fn to_guard(&self) -> String {
+		return format!("{} = {}", self.name, expression_to_string(&self.value));
 	}
 }
 
+/// Every line of `text` one tab further right
+fn indented(text: &str) -> String {
+	return text.lines().map(|line| format!("\t{}\n", line)).collect();
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Warning {
 	pub test: EvaluableExpr,
 	pub message: Option<String>,
 	pub hint: Option<String>,
+	pub docs: Option<String>,
 }
 
 impl Warning {
@@ -378,17 +436,17 @@ impl Warning {
 	{
 		Test: "a + 5 == c",
 		Message: "a + 5 should be equal to c",
-		Hint: "Check the value of a and c"
+		Hint: "Check the value of a and c",
+		Docs: "https://example.com/docs/c"
 	}
-	Message and Hints are optional
+	Message, Hint and Docs are optional, and so is Test if the call should always be reported
 	*/
-	pub fn from_string(s: &str) -> Warning {
-		// let parts: Vec<&str> = s.split(",").collect();
+	pub fn from_string(s: &str) -> Result<Warning, String> {
 		let mut parts = Vec::new();
 		// find all the commas, if they are inside a string, ignore them
 		let mut start = 0;
 		let mut inside_string = false;
-		for (i, c) in s.chars().enumerate() {
+		for (i, c) in s.char_indices() {
 			if c == '"' {
 				inside_string = !inside_string;
 			}
@@ -399,58 +457,167 @@ impl Warning {
 		}
 		parts.push(&s[start..]);
 
+		// the value of a field, without its quotes for the strings
+		let field = |part: &str, quoted: bool| {
+			let Some((_, value)) = part.split_once(':')
+			else {
+				return Err(format!("Expected `Field: value` in a warning, found: {:?}", part));
+			};
+			let value = value.trim();
+			if !quoted {
+				return Ok(value.to_string());
+			}
+			match value.strip_prefix('"').and_then(|value| value.strip_suffix('"')) {
+				Some(value) => return Ok(value.to_string()),
+				None => return Err(format!("Expected a quoted string, found: {:?}", value)),
+			}
+		};
 		let mut test = None;
 		let mut message = None;
 		let mut hint = None;
+		let mut docs = None;
 		for part in &parts {
 			let part = part.trim();
-			if part.starts_with("Test") {
-				let split = part.split(":").collect::<Vec<&str>>();
-				let test_expr = split[1].trim();
-				let tokens = Token::from_string(test_expr);
-				let rpn = expr_tokens_to_rpn(tokens);
-				println!("RPN: {:#?}", rpn);
-				if test.is_some() {
-					panic!("Multiple tests in warning");
-				}
-				test = Some(rpn);
+			if part.is_empty() {
+				continue;
 			}
-			if part.starts_with("Message") {
-				let split = part.split(":").collect::<Vec<&str>>();
-				if message.is_some() {
-					panic!("Multiple messages in warning");
+			let (slot, name) = match part {
+				part if part.starts_with("Test") => {
+					if test.is_some() {
+						return Err("Multiple tests in warning".to_string());
+					}
+					let rpn = parse_expression(&field(part, false)?)?;
+					debug!("RPN: {:#?}", rpn);
+					test = Some(rpn);
+					continue;
 				}
-				message = Some(split[1].trim().to_string());
-				message = Some(message.as_ref().unwrap()[1..message.as_ref().unwrap().len() - 1].to_string());
-			}
-			if part.starts_with("Hint") {
-				let split = part.split(":").collect::<Vec<&str>>();
-				if hint.is_some() {
-					panic!("Multiple hints in warning");
+				part if part.starts_with("Message") => (&mut message, "messages"),
+				part if part.starts_with("Hint") => (&mut hint, "hints"),
+				part if part.starts_with("Docs") => {
+					if docs.is_some() {
+						return Err("Multiple docs in warning".to_string());
+					}
+					// the quotes of a link are optional
+					docs = Some(field(part, false)?.trim_matches('"').to_string());
+					continue;
 				}
-				hint = Some(split[1].trim().to_string());
-				// remove the first " and last "
-				hint = Some(hint.as_ref().unwrap()[1..hint.as_ref().unwrap().len() - 1].to_string());
-			}
-		}
-		println!("TEST: {:#?}", test);
-		println!("MESSAGE: {:#?}", message);
-		println!("HINT: {:#?}", hint);
-		return Warning {
-			test: test.unwrap(),
-			message,
-			hint,
-		};
+				part => return Err(format!("Unknown warning field: {:?}", part)),
+			};
+			if slot.is_some() {
+				return Err(format!("Multiple {} in warning", name));
+			}
+			*slot = Some(field(part, true)?);
+		}
+		debug!("TEST: {:#?}", test);
+		debug!("MESSAGE: {:#?}", message);
+		debug!("HINT: {:#?}", hint);
+		// Without a test, the warning is triggered every time the call is made
+		let test = test.unwrap_or_else(|| vec![Token::Operand(Identifier::const_bool(true))]);
+		return Ok(Warning { test, message, hint, docs });
+	}
+
+	/// As written in a guard, the block of `Warn` or `Denies` holding it
+	pub fn to_guard(&self) -> String {
+		let mut parts = vec![format!("Test: {}", expression_to_string(&self.test))];
+		parts.extend(self.message.iter().map(|message| format!("Message: \"{}\"", message)));
+		parts.extend(self.hint.iter().map(|hint| format!("Hint: \"{}\"", hint)));
+		parts.extend(self.docs.iter().map(|docs| format!("Docs: \"{}\"", docs)));
+		return format!("{{\n{}}}\n", indented(&parts.join(",\n")));
 	}
 }
 
 pub type Deny = Warning;
 
+/// What a call does when it returns a given value, `== 0: Does { ... }` or `else: Does { ... }` in `Returns`
+#[derive(Debug, PartialEq, Clone)]
+pub struct Outcome {
+	/// The test of the returned value, e.g. `(NotEquals, 0)` for `!= 0`, None for `else`
+	pub returned: Option<(Operator, i32)>,
+	pub does: Vec<Assignement>,
+}
+
+impl Outcome {
+	/*
+		== 0: Does {
+			indexed = true
+		}
+	*/
+	fn from_bracketed(bracketed: &Bracketed) -> Result<Outcome, String> {
+		let header = bracketed.beginning_line.line.trim_end_matches('{').trim();
+		let Some((test, section)) = header.split_once(':')
+		else {
+			return Err(format!(
+				"Expected `<test>: Does {{` in Returns, found: {:?}",
+				bracketed.beginning_line.line
+			));
+		};
+		if section.trim() != "Does" {
+			return Err(format!("Unknown Returns section: {:?}", section.trim()));
+		}
+		let test = test.trim();
+		let returned = if test == "else" {
+			None
+		}
+		else {
+			let value_start = test.find(|c: char| !"=!<>".contains(c)).unwrap_or(test.len());
+			let (op, value) = test.split_at(value_start);
+			let op = match Operator::from_string(op) {
+				Ok(op) if op == Operator::Equals || op.is_comparison() => op,
+				_ => return Err(format!("Expected a comparison in Returns, found: {:?}", test)),
+			};
+			let value = match value.trim() {
+				"NULL" | "false" => 0,
+				"true" => 1,
+				value => value
+					.parse()
+					.map_err(|_| format!("Expected a number in Returns, found: {:?}", value))?,
+			};
+			Some((op, value))
+		};
+		let does = match &bracketed.content {
+			Either::Left(s) => s
+				.lines()
+				.filter(|line| !line.trim().is_empty())
+				.map(Assignement::from_string)
+				.collect::<Result<_, _>>()?,
+			Either::Right(_) => return Err("Returns content is not a string".to_string()),
+		};
+		return Ok(Outcome { returned, does });
+	}
+
+	/// As written in `Returns`, e.g. `== 0: Does { ... }`
+	pub fn to_guard(&self) -> String {
+		let test = match self.returned {
+			Some((op, value)) => format!("{} {}", op.as_str(), value),
+			None => "else".to_string(),
+		};
+		let does = self
+			.does
+			.iter()
+			.map(|assignement| assignement.to_guard() + "\n")
+			.collect::<String>();
+		return format!("{}: Does {{\n{}}}\n", test, indented(&does));
+	}
+
+	/// Whether it is the outcome of a call returning `value`
+	fn matches(&self, value: i32) -> bool {
+		match self.returned {
+			Some((Operator::Equals, expected)) => return value == expected,
+			Some((op, expected)) => return op.compare(&value, &expected),
+			None => return true,
+		}
+	}
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct Call {
 	pub name: String,
 	pub does: Option<Vec<Assignement>>,
 	pub warn: Option<Vec<Warning>>,
 	pub deny: Option<Vec<Deny>>,
+	/// What it does depending on what it returns, the first outcome matching applies,
+	/// or any of the ones written with the same test
+	pub returns: Option<Vec<Outcome>>,
 }
 
 impl Call {
@@ -472,221 +639,1043 @@ impl Call {
 			}
 		}
 	*/
-	pub fn from_bracketed(bracketed: Bracketed) -> Call {
-		println!(" FROM BRACKETED ");
+	pub fn from_bracketed(bracketed: Bracketed) -> Result<Call, String> {
+		debug!(" FROM BRACKETED ");
 		let mut does = None;
 		let mut warn = None;
 		let mut deny = None;
-		if let Either::Right(v) = bracketed.content {
-			for b in &v {
+		let mut returns = None;
+		if let Either::Right(v) = &bracketed.content {
+			for b in v {
+				let located = |message: String| b.beginning_line.located(&message);
 				match b.beginning_line.line.trim() {
 					bs if bs.starts_with("Does") => {
-						let mut does_vec = Vec::new();
-						let content = &b.content;
-						match content {
-							Either::Right(v) => {
-								panic!("Does content is not a string");
-							}
-							Either::Left(s) => {
-								let lines = s.lines().map(|line| line.to_string()).collect::<Vec<_>>();
-								for line in lines {
-									does_vec.push(Assignement::from_string(&line));
-								}
-							}
-						}
-						does = Some(does_vec);
+						let Either::Left(s) = &b.content
+						else {
+							return Err(located("Does content is not a string".to_string()));
+						};
+						let assignements = s
+							.lines()
+							.filter(|line| !line.trim().is_empty())
+							.map(Assignement::from_string)
+							.collect::<Result<_, _>>();
+						does = Some(assignements.map_err(located)?);
 					}
 					bs if bs.starts_with("Warn") => {
-						let mut warn_vec = Vec::new();
-						match &b.content {
-							Either::Right(v) => {
-								for b in v {
-									for x in v {
-										match &x.content {
-											Either::Right(v) => {
-												panic!("Warn content is not a string");
-											}
-											Either::Left(s) => {
-												warn_vec.push(Warning::from_string(s));
-											}
-										}
-									}
-								}
-							}
-							Either::Left(s) => panic!("Warn content is not a string"),
-						}
-						warn = Some(warn_vec);
+						warn = Some(Call::warnings_from_bracketed(b, "Warn").map_err(located)?);
+					}
+					bs if bs.starts_with("Denies") => {
+						deny = Some(Call::warnings_from_bracketed(b, "Denies").map_err(located)?);
 					}
-					_ => panic!("Unknown bracketed content: {:?}", b.beginning_line.line),
+					bs if bs.starts_with("Returns") => {
+						returns = Some(match &b.content {
+							Either::Right(outcomes) => outcomes
+								.iter()
+								.map(|outcome| Outcome::from_bracketed(outcome).map_err(|e| outcome.beginning_line.located(&e)))
+								.collect::<Result<_, _>>()?,
+							Either::Left(_) => Vec::new(),
+						});
+					}
+					_ => return Err(located(format!("Unknown bracketed content: {:?}", b.beginning_line.line))),
 				}
 			}
 		}
-		return Call {
+		let call = Call {
 			name: bracketed.beginning_line.line.clone(),
 			does,
 			warn,
 			deny,
+			returns,
+		};
+		if let Some(position) = call.self_position() {
+			indirection(call.arguments()[position]).map_err(|e| bracketed.beginning_line.located(&e))?;
+		}
+		return Ok(call);
+	}
+
+	/// The meta data its rules read or set, e.g. `["sorted"]`
+	pub fn meta_names(&self) -> Vec<String> {
+		let mut names = Vec::new();
+		let outcomes = self.returns.iter().flatten().flat_map(|outcome| &outcome.does);
+		for assignement in self.does.iter().flatten().chain(outcomes) {
+			names.push(assignement.name.clone());
+			names.extend(expression_variables(&assignement.value));
+		}
+		for warning in self.warn.iter().chain(&self.deny).flatten() {
+			names.extend(expression_variables(&warning.test));
+		}
+		let mut unique = Vec::new();
+		for name in names {
+			if !unique.contains(&name) {
+				unique.push(name);
+			}
+		}
+		return unique;
+	}
+
+	/// A call with no rule of its own, e.g. a destructor `IntVector_free(&$self)`
+	pub fn from_signature(signature: &str) -> Call {
+		return Call {
+			name: signature.to_string(),
+			does: None,
+			warn: None,
+			deny: None,
+			returns: None,
+		};
+	}
+
+	/// The outcomes the call can have when what it returns passes `test`, or whatever it returns if None.
+	/// None stands for returning a value no outcome matches
+	pub fn outcomes(&self, test: Option<(Operator, i32)>) -> Vec<Option<&Outcome>> {
+		let outcomes = self.returns.as_deref().unwrap_or_default();
+		// the values around the ones compared to stand for every value
+		let mut values = vec![i32::MIN, i32::MAX];
+		for (_, value) in outcomes.iter().filter_map(|outcome| outcome.returned).chain(test) {
+			values.extend([value.checked_sub(1), Some(value), value.checked_add(1)].into_iter().flatten());
+		}
+		let test = test.map(|returned| Outcome {
+			returned: Some(returned),
+			does: Vec::new(),
+		});
+		let mut possible = Vec::new();
+		for value in values {
+			if test.as_ref().is_some_and(|test| !test.matches(value)) {
+				continue;
+			}
+			let Some(first) = outcomes.iter().position(|outcome| outcome.matches(value))
+			else {
+				if !possible.contains(&None) {
+					possible.push(None);
+				}
+				continue;
+			};
+			// the outcomes with the same test are alternatives
+			for index in (first..outcomes.len()).filter(|&index| outcomes[index].returned == outcomes[first].returned) {
+				if !possible.contains(&Some(index)) {
+					possible.push(Some(index));
+				}
+			}
+		}
+		// in the order of the guard, no outcome last
+		possible.sort_by_key(|outcome| outcome.unwrap_or(usize::MAX));
+		return possible.into_iter().map(|outcome| outcome.map(|index| &outcomes[index])).collect();
+	}
+
+	fn warnings_from_bracketed(bracketed: &Bracketed, section: &str) -> Result<Vec<Warning>, String> {
+		let mut warnings = Vec::new();
+		match &bracketed.content {
+			Either::Right(v) => {
+				for x in v {
+					match &x.content {
+						Either::Right(_) => return Err(format!("{} content is not a string", section)),
+						Either::Left(s) => warnings.push(Warning::from_string(s)?),
+					}
+				}
+			}
+			Either::Left(_) => return Err(format!("{} content is not a string", section)),
+		}
+		return Ok(warnings);
+	}
+
+	/// The call as written in a guard, with its rules
+	pub fn to_guard(&self) -> String {
+		let mut sections = String::new();
+		if let Some(does) = &self.does {
+			let does = does.iter().map(|assignement| assignement.to_guard() + "\n").collect::<String>();
+			sections += &format!("Does {{\n{}}}\n", indented(&does));
+		}
+		if let Some(returns) = &self.returns {
+			let outcomes = returns.iter().map(Outcome::to_guard).collect::<String>();
+			sections += &format!("Returns {{\n{}}}\n", indented(&outcomes));
+		}
+		for (section, warnings) in [("Denies", &self.deny), ("Warn", &self.warn)] {
+			if let Some(warnings) = warnings {
+				let warnings = warnings.iter().map(Warning::to_guard).collect::<String>();
+				sections += &format!("{} {{\n{}}}\n", section, indented(&warnings));
+			}
+		}
+		return format!("{} {{\n{}}}\n", self.signature(), indented(&sections));
+	}
+
+	/// The call as written in the guard, without the opening bracket
+	/// e.g. `IntVector_push_back(&$self, ...)`
+	pub fn signature(&self) -> &str {
+		return self.name.trim_end_matches('{').trim();
+	}
+
+	/// The name of the called function, e.g. `IntVector_push_back`
+	pub fn function_name(&self) -> &str {
+		let signature = self.signature();
+		return signature.split('(').next().unwrap().trim();
+	}
+
+	/// The argument patterns of the call, e.g. `["&$self", "..."]`
+	pub fn arguments(&self) -> Vec<&str> {
+		let signature = self.signature();
+		let (Some(open), Some(close)) = (signature.find('('), signature.rfind(')'))
+		else {
+			return Vec::new();
+		};
+		return signature[open + 1..close]
+			.split(',')
+			.map(|argument| argument.trim())
+			.filter(|argument| !argument.is_empty())
+			.collect();
+	}
+
+	/// Index of the argument bound to `$self`, if the call is made on a DataStruct
+	pub fn self_position(&self) -> Option<usize> {
+		return self.arguments().iter().position(|argument| argument.contains("$self"));
+	}
+
+	/// Index of the argument bound to `$self` and how many times its address is taken,
+	/// `&$self` is 1, `$self` is 0 and `*$self` is -1
+	pub fn self_pattern(&self) -> Option<(usize, isize)> {
+		let position = self.self_position()?;
+		// checked when the guard is parsed
+		let indirection = indirection(self.arguments()[position]).ok()?;
+		return Some((position, indirection));
+	}
+}
+
+/// How many times the address of `$self` is taken in an argument pattern, e.g. 1 for `&$self`
+fn indirection(pattern: &str) -> Result<isize, String> {
+	let mut indirection = 0;
+	for c in pattern[..pattern.find("$self").unwrap()].chars() {
+		match c {
+			'&' => indirection += 1,
+			'*' => indirection -= 1,
+			'(' | ')' => {}
+			c if c.is_whitespace() => {}
+			_ => return Err(format!("Unsupported $self pattern: {}", pattern)),
+		}
+	}
+	return Ok(indirection);
+}
+
+/// How the C code holds a DataStruct
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum HandleKind {
+	/// The struct itself, `IntVector vec`
+	Value,
+	/// A pointer to it, `Graph* g`, or an opaque pointer, `typedef struct G* GraphHandle`
+	Pointer,
+	/// A descriptor, `int fd`
+	Integer,
+}
+
+/// What a call to a function no guard or summary describes does to the objects it is given
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Escape {
+	/// Their meta data can be anything after it
+	Havoc,
+	/// They are left as they are
+	Keep,
+	/// Reported, and left as they are
+	Warn,
+}
+
+impl Escape {
+	pub fn from_string(escape: &str) -> Result<Escape, String> {
+		match escape {
+			"havoc" => Ok(Escape::Havoc),
+			"keep" => Ok(Escape::Keep),
+			"warn" => Ok(Escape::Warn),
+			_ => Err(format!("Unknown escape policy: {}", escape)),
+		}
+	}
+}
+
+/// The C type the DataStruct is handled through, `$self` is a variable of that type
+#[derive(Debug, PartialEq, Clone)]
+pub struct Handle {
+	pub kind: HandleKind,
+	/// The type without its pointers, e.g. `Graph` for `Graph*`
+	pub type_name: String,
+	/// The pointers written in the type, e.g. 1 for `Graph*`
+	pub pointer_depth: usize,
+	/// A constructor can give NULL, which has to be checked before the handle is used
+	pub nullable: bool,
+	/// What unknown functions do to it, the one of the options if None
+	pub escapes: Option<Escape>,
+}
+
+impl Handle {
+	/*
+	Handle {
+		Kind: pointer
+		Type: Graph*
+		Nullable: false
+		Escapes: warn
+	}
+	*/
+	/// The handle described by the lines of a `Handle` section, the DataStruct itself if there are none
+	pub fn from_lines(data_struct: &str, lines: &[String]) -> Result<Handle, String> {
+		let mut kind = HandleKind::Value;
+		let mut type_name = None;
+		let mut nullable = None;
+		let mut escapes = None;
+		for line in lines {
+			let Some((key, value)) = line.split_once(':')
+			else {
+				return Err(format!("Expected `Key: value` in Handle, found: {:?}", line));
+			};
+			let value = value.trim().trim_end_matches(',');
+			match key.trim() {
+				"Kind" => {
+					kind = match value {
+						"value" => HandleKind::Value,
+						"pointer" => HandleKind::Pointer,
+						"integer" => HandleKind::Integer,
+						_ => return Err(format!("Unknown handle kind: {:?}", value)),
+					}
+				}
+				"Type" => type_name = Some(value.to_string()),
+				"Nullable" => {
+					nullable = match value {
+						"true" => Some(true),
+						"false" => Some(false),
+						_ => return Err(format!("Invalid Nullable value: {:?}", value)),
+					}
+				}
+				"Escapes" => escapes = Some(Escape::from_string(value)?),
+				_ => return Err(format!("Unknown Handle field: {:?}", key)),
+			}
+		}
+		let type_name = match (type_name, kind) {
+			(Some(type_name), _) => type_name,
+			(None, HandleKind::Pointer) => format!("{}*", data_struct),
+			(None, HandleKind::Integer) => "int".to_string(),
+			(None, HandleKind::Value) => data_struct.to_string(),
 		};
+		let pointer_depth = type_name.matches('*').count();
+		return Ok(Handle {
+			kind,
+			type_name: type_name.trim_end_matches(['*', ' ']).to_string(),
+			pointer_depth,
+			nullable: nullable.unwrap_or(kind == HandleKind::Pointer),
+			escapes,
+		});
 	}
 }
 
+#[derive(Debug, PartialEq, Clone)]
 pub struct DataStruct {
 	pub name: String,
 	pub constructors: Vec<String>,
 	pub destructors: Vec<String>,
 	pub meta_data: Vec<MetaData>,
 	pub calls: Vec<Call>,
+	/// Written `DataStruct IntVector NoCopy {`, copies of it are reported
+	pub no_copy: bool,
+	pub handle: Handle,
+}
+
+impl DataStruct {
+	/*
+	DataStruct IntVector {
+		Constructors {
+			IntVector_new()
+		}
+		Destructors {
+			IntVector_free(&$self)
+		}
+		Handle {
+			Kind: value
+		}
+		Meta {
+			sorted: bool = true
+		}
+		Calls {
+			...
+		}
+	}
+	*/
+	pub fn from_bracketed(bracketed: Bracketed) -> Result<DataStruct, String> {
+		return DataStruct::parse(bracketed, true);
+	}
+
+	/// The DataStruct of a bracketed block, its calls only reading and setting its meta data if `check_meta`
+	fn parse(bracketed: Bracketed, check_meta: bool) -> Result<DataStruct, String> {
+		let header_line = &bracketed.beginning_line;
+		let header = header_line.line.trim_end_matches('{').trim();
+		let mut words = match header.strip_prefix("DataStruct") {
+			Some(rest) => rest.split_whitespace(),
+			None => return Err(header_line.located(&format!("Expected a DataStruct, found: {:?}", header_line.line))),
+		};
+		let name = match words.next() {
+			Some(name) => name.to_string(),
+			None => return Err(header_line.located(&format!("Missing the name of the DataStruct: {:?}", header_line.line))),
+		};
+		let mut no_copy = false;
+		for word in words {
+			match word {
+				"NoCopy" => no_copy = true,
+				_ => return Err(header_line.located(&format!("Unknown DataStruct attribute: {:?}", word))),
+			}
+		}
+		let mut constructors = Vec::new();
+		let mut destructors = Vec::new();
+		let mut meta_data = Vec::new();
+		let mut calls = Vec::new();
+		let mut handle = Handle::from_lines(&name, &[])?;
+		let mut call_lines = Vec::new();
+		let sections = match bracketed.content {
+			Either::Right(v) => v,
+			Either::Left(_) => Vec::new(),
+		};
+		for b in sections {
+			let lines = match &b.content {
+				Either::Left(s) => s
+					.lines()
+					.map(|line| line.trim().to_string())
+					.filter(|line| !line.is_empty())
+					.collect(),
+				Either::Right(_) => Vec::new(),
+			};
+			let located = |message: String| b.beginning_line.located(&message);
+			match b.beginning_line.line.trim() {
+				bs if bs.starts_with("Constructors") => constructors = lines,
+				bs if bs.starts_with("Destructors") => {
+					for destructor in lines.iter().filter(|destructor| destructor.contains("$self")) {
+						let call = Call::from_signature(destructor);
+						indirection(call.arguments()[call.self_position().unwrap()]).map_err(located)?;
+					}
+					destructors = lines;
+				}
+				bs if bs.starts_with("Handle") => handle = Handle::from_lines(&name, &lines).map_err(located)?,
+				bs if bs.starts_with("Meta") => {
+					let meta = lines.iter().map(|line| MetaData::from_string(line)).collect::<Result<_, _>>();
+					meta_data = meta.map_err(located)?;
+				}
+				bs if bs.starts_with("Calls") => {
+					if let Either::Right(v) = b.content {
+						for call in v {
+							call_lines.push(call.beginning_line.clone());
+							calls.push(Call::from_bracketed(call)?);
+						}
+					}
+				}
+				_ => return Err(located(format!("Unknown bracketed content: {:?}", b.beginning_line.line))),
+			}
+		}
+		for (call, line) in calls.iter().zip(&call_lines).filter(|_| check_meta) {
+			let unknown = call
+				.meta_names()
+				.into_iter()
+				.find(|name| meta_data.iter().all(|meta| &meta.name != name));
+			if let Some(unknown) = unknown {
+				return Err(line.located(&format!("Unknown meta data {:?}", unknown)));
+			}
+		}
+		return Ok(DataStruct {
+			name,
+			constructors,
+			destructors,
+			meta_data,
+			calls,
+			no_copy,
+			handle,
+		});
+	}
+
+	/// Whether `function` is one of the constructors, e.g. `IntVector_new`
+	pub fn is_constructor(&self, function: &str) -> bool {
+		return self
+			.constructors
+			.iter()
+			.any(|constructor| constructor.split('(').next().unwrap().trim() == function);
+	}
+
+	pub fn destructor_calls(&self) -> Vec<Call> {
+		return self.destructors.iter().map(|destructor| Call::from_signature(destructor)).collect();
+	}
+
+	/// Parses every DataStruct of a guard file
+	pub fn parse_guards(guard_file: &str) -> Result<Vec<DataStruct>, String> {
+		return parse_bracketed(guard_file)?
+			.iter()
+			.map(|brack| fill_blanks(brack, guard_file))
+			.map(DataStruct::from_bracketed)
+			.collect();
+	}
+
+	/// Parses every DataStruct of a summary file, which uses the meta data of guards without declaring them
+	pub fn parse_summary_file(summary_file: &str) -> Result<Vec<DataStruct>, String> {
+		return parse_bracketed(summary_file)?
+			.iter()
+			.map(|brack| DataStruct::parse(fill_blanks(brack, summary_file), false))
+			.collect();
+	}
 }
 
+/// `expression` evaluated after `assignement`, in terms of the values before it:
+/// `sorted == false` after `sorted = !reversed` is `!reversed == false`
+pub fn substitute(expression: &EvaluableExpr, assignement: &Assignement) -> EvaluableExpr {
+	let mut substituted = Vec::new();
+	for token in expression {
+		match token {
+			Token::Operand(Identifier::Variable { name }) if *name == assignement.name => {
+				substituted.extend(assignement.value.iter().cloned())
+			}
+			token => substituted.push(token.clone()),
+		}
+	}
+	return substituted;
+}
+
+/// An expression as written in a guard, with only the parentheses it needs
+pub fn expression_to_string(expression: &EvaluableExpr) -> String {
+	// the operands written so far, with the priority of their last operator
+	let mut stack: Vec<(String, i32)> = Vec::new();
+	for token in expression {
+		match token {
+			Token::Operand(Identifier::Variable { name }) => stack.push((name.clone(), i32::MAX)),
+			Token::Operand(Identifier::Constant(constant)) => stack.push(match constant {
+				Constant::Bool(b) => (b.to_string(), i32::MAX),
+				Constant::Number(n) if *n < 0 => (n.to_string(), Operator::UnaryMinus.priority()),
+				Constant::Number(n) => (n.to_string(), i32::MAX),
+				Constant::String(s) => (format!("\"{}\"", s), i32::MAX),
+			}),
+			Token::Operation(op) => {
+				let priority = op.priority();
+				// an operand is parenthesized when its operator comes after this one
+				let operand = |(text, _): (String, i32), after: bool| match after {
+					true => format!("({})", text),
+					false => text,
+				};
+				match op.arity_and_types() {
+					ArityAndTypes::Unary(_) => {
+						let value = stack.pop().unwrap();
+						let after = value.1 < priority;
+						stack.push((format!("{}{}", op.as_str(), operand(value, after)), priority));
+					}
+					ArityAndTypes::Binary(_, _) => {
+						let right = stack.pop().unwrap();
+						let left = stack.pop().unwrap();
+						// the operators of the same priority are evaluated from left to right
+						let (left_after, right_after) = (left.1 < priority, right.1 <= priority);
+						let text = format!("{} {} {}", operand(left, left_after), op.as_str(), operand(right, right_after));
+						stack.push((text, priority));
+					}
+				}
+			}
+			Token::Parenthesis(_) => panic!("Invalid token {:?}", token),
+		}
+	}
+	return stack.pop().map(|(text, _)| text).unwrap_or_default();
+}
+
+/// The meta data read by an expression
+pub fn expression_variables(expression: &EvaluableExpr) -> Vec<String> {
+	let mut names = Vec::new();
+	for token in expression {
+		if let Token::Operand(Identifier::Variable { name }) = token {
+			if !names.contains(name) {
+				names.push(name.clone());
+			}
+		}
+	}
+	return names;
+}
+
+/// The most values a bool or string meta data is followed with before it is considered unknown
+const MAX_VALUES: usize = 8;
+
+/// What a meta data can be on the paths reaching a point of the program
 #[derive(Debug, PartialEq, Clone)]
-pub struct Environment {
-	pub assocs: Vec<(String, Identifier)>,
+pub enum AbstractValue {
+	/// One of these values, never empty, for the bool and string meta data
+	OneOf(Vec<Constant>),
+	/// A number between `min` and `max` included, None for no bound
+	Interval { min: Option<i32>, max: Option<i32> },
+	/// Anything, e.g. when it can take too many values
+	Unknown,
 }
 
-impl Environment {
-	pub fn new() -> Environment {
-		Environment { assocs: Vec::new() }
+/// Whether a number of the first interval can be lower than, equal to and greater than one of the second
+fn compare_intervals((min, max): (Option<i32>, Option<i32>), (other_min, other_max): (Option<i32>, Option<i32>)) -> (bool, bool, bool) {
+	let lower = |a: Option<i32>, b: Option<i32>| a.zip(b).is_none_or(|(a, b)| a < b);
+	let lower_or_equal = |a: Option<i32>, b: Option<i32>| a.zip(b).is_none_or(|(a, b)| a <= b);
+	let can_be_lower = lower(min, other_max);
+	let can_be_greater = lower(other_min, max);
+	let can_be_equal = lower_or_equal(min, other_max) && lower_or_equal(other_min, max);
+	return (can_be_lower, can_be_equal, can_be_greater);
+}
+
+impl AbstractValue {
+	fn constant(constant: &Constant) -> AbstractValue {
+		match constant {
+			Constant::Number(n) => {
+				return AbstractValue::Interval {
+					min: Some(*n),
+					max: Some(*n),
+				}
+			}
+			constant => return AbstractValue::OneOf(vec![constant.clone()]),
+		}
 	}
 
-	pub fn evaluate_assignement(&mut self, assignement: Assignement) {
-		let result = self.evaluate_rpn(assignement.value);
-		for i in 0..self.assocs.len() {
-			if self.assocs[i].0 == assignement.name {
-				self.assocs[i].1 = result;
-				return;
+	fn from_bools(can_be_true: bool, can_be_false: bool) -> AbstractValue {
+		let mut values = Vec::new();
+		if can_be_true {
+			values.push(Constant::Bool(true));
+		}
+		if can_be_false {
+			values.push(Constant::Bool(false));
+		}
+		return AbstractValue::OneOf(values);
+	}
+
+	/// Whether it can be true and whether it can be false, a number is true when it is not 0 as in C
+	fn bools(&self) -> (bool, bool) {
+		match self {
+			AbstractValue::OneOf(values) => return (values.contains(&Constant::Bool(true)), values.contains(&Constant::Bool(false))),
+			AbstractValue::Interval { min, max } => {
+				let (lower, equal, greater) = compare_intervals((*min, *max), (Some(0), Some(0)));
+				return (lower || greater, equal);
+			}
+			AbstractValue::Unknown => return (true, true),
+		}
+	}
+
+	fn interval(&self) -> Option<(Option<i32>, Option<i32>)> {
+		match self {
+			AbstractValue::Interval { min, max } => return Some((*min, *max)),
+			_ => return None,
+		}
+	}
+
+	fn join(&self, other: &AbstractValue) -> AbstractValue {
+		match (self, other) {
+			(AbstractValue::OneOf(values), AbstractValue::OneOf(others)) => {
+				let mut values = values.clone();
+				for value in others {
+					if !values.contains(value) {
+						values.push(value.clone());
+					}
+				}
+				if values.len() > MAX_VALUES {
+					return AbstractValue::Unknown;
+				}
+				return AbstractValue::OneOf(values);
+			}
+			(
+				AbstractValue::Interval { min, max },
+				AbstractValue::Interval {
+					min: other_min,
+					max: other_max,
+				},
+			) => {
+				return AbstractValue::Interval {
+					min: min.zip(*other_min).map(|(a, b)| a.min(b)),
+					max: max.zip(*other_max).map(|(a, b)| a.max(b)),
+				};
+			}
+			_ => return AbstractValue::Unknown,
+		}
+	}
+
+	/// Like `join`, but the bounds of an interval that move are dropped
+	fn widen(&self, other: &AbstractValue) -> AbstractValue {
+		match (self.join(other), self) {
+			(
+				AbstractValue::Interval { min, max },
+				AbstractValue::Interval {
+					min: previous_min,
+					max: previous_max,
+				},
+			) => {
+				return AbstractValue::Interval {
+					min: min.filter(|_| min == *previous_min),
+					max: max.filter(|_| max == *previous_max),
+				};
+			}
+			(joined, _) => return joined,
+		}
+	}
+
+	fn unary(&self, op: Operator) -> AbstractValue {
+		match (op, self) {
+			(Operator::UnaryMinus, AbstractValue::Interval { min, max }) => {
+				return AbstractValue::Interval {
+					min: max.and_then(i32::checked_neg),
+					max: min.and_then(i32::checked_neg),
+				};
+			}
+			(Operator::Not, value) => {
+				let (can_be_true, can_be_false) = value.bools();
+				return AbstractValue::from_bools(can_be_false, can_be_true);
 			}
+			_ => return AbstractValue::Unknown,
 		}
-		self.assocs.push((assignement.name, result));
 	}
 
-	pub fn fetch(&self, name: &str) -> Identifier {
-		for (n, id) in &self.assocs {
+	fn binary(&self, op: Operator, other: &AbstractValue) -> AbstractValue {
+		use Operator::*;
+		match op {
+			And | Or => {
+				let (true1, false1) = self.bools();
+				let (true2, false2) = other.bools();
+				if op == And {
+					return AbstractValue::from_bools(true1 && true2, false1 || false2);
+				}
+				return AbstractValue::from_bools(true1 || true2, false1 && false2);
+			}
+			Equals | NotEquals if self.interval().is_none() || other.interval().is_none() => {
+				let (AbstractValue::OneOf(values), AbstractValue::OneOf(others)) = (self, other)
+				else {
+					return AbstractValue::from_bools(true, true);
+				};
+				let equal = values.iter().any(|value| others.contains(value));
+				let different = values.iter().any(|value| others.iter().any(|other| other != value));
+				if op == Equals {
+					return AbstractValue::from_bools(equal, different);
+				}
+				return AbstractValue::from_bools(different, equal);
+			}
+			_ => {}
+		}
+		let (Some((min, max)), Some((other_min, other_max))) = (self.interval(), other.interval())
+		else {
+			return AbstractValue::Unknown;
+		};
+		let (lower, equal, greater) = compare_intervals((min, max), (other_min, other_max));
+		let bounds = |values: [Option<i32>; 4]| {
+			if values.iter().any(Option::is_none) {
+				return AbstractValue::Interval { min: None, max: None };
+			}
+			return AbstractValue::Interval {
+				min: values.iter().flatten().min().copied(),
+				max: values.iter().flatten().max().copied(),
+			};
+		};
+		let corners = |f: fn(i32, i32) -> Option<i32>| {
+			let at = |a: Option<i32>, b: Option<i32>| a.zip(b).and_then(|(a, b)| f(a, b));
+			return bounds([at(min, other_min), at(min, other_max), at(max, other_min), at(max, other_max)]);
+		};
+		match op {
+			Addition => {
+				return AbstractValue::Interval {
+					min: min.zip(other_min).and_then(|(a, b)| a.checked_add(b)),
+					max: max.zip(other_max).and_then(|(a, b)| a.checked_add(b)),
+				};
+			}
+			Substraction => {
+				return AbstractValue::Interval {
+					min: min.zip(other_max).and_then(|(a, b)| a.checked_sub(b)),
+					max: max.zip(other_min).and_then(|(a, b)| a.checked_sub(b)),
+				};
+			}
+			Multiplication => return corners(i32::checked_mul),
+			// the divisor may be 0
+			Division if compare_intervals((Some(0), Some(0)), (other_min, other_max)).1 => {
+				return AbstractValue::Interval { min: None, max: None };
+			}
+			Division => return corners(i32::checked_div),
+			Equals => return AbstractValue::from_bools(equal, lower || greater),
+			NotEquals => return AbstractValue::from_bools(lower || greater, equal),
+			Lower => return AbstractValue::from_bools(lower, equal || greater),
+			LowerOrEqual => return AbstractValue::from_bools(lower || equal, greater),
+			Greater => return AbstractValue::from_bools(greater, lower || equal),
+			GreaterOrEqual => return AbstractValue::from_bools(greater || equal, lower),
+			_ => return AbstractValue::Unknown,
+		}
+	}
+
+	/// The value if it is known exactly
+	pub fn exact(&self) -> Option<Constant> {
+		match self {
+			AbstractValue::OneOf(values) if values.len() == 1 => return Some(values[0].clone()),
+			AbstractValue::Interval {
+				min: Some(min),
+				max: Some(max),
+			} if min == max => return Some(Constant::Number(*min)),
+			_ => return None,
+		}
+	}
+}
+
+impl fmt::Display for Constant {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Constant::Bool(b) => write!(f, "{}", b),
+			Constant::Number(n) => write!(f, "{}", n),
+			Constant::String(s) => write!(f, "{:?}", s),
+		}
+	}
+}
+
+/// The value if it is known exactly, otherwise what it can be, e.g. `true | false`, `[0, +inf]` or `unknown`
+impl fmt::Display for AbstractValue {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if let Some(value) = self.exact() {
+			return write!(f, "{}", value);
+		}
+		match self {
+			AbstractValue::OneOf(values) => {
+				let values = values.iter().map(|value| value.to_string()).collect::<Vec<_>>();
+				return write!(f, "{}", values.join(" | "));
+			}
+			AbstractValue::Interval { min, max } => {
+				let min = min.map_or("-inf".to_string(), |min| min.to_string());
+				let max = max.map_or("+inf".to_string(), |max| max.to_string());
+				return write!(f, "[{}, {}]", min, max);
+			}
+			AbstractValue::Unknown => return write!(f, "unknown"),
+		}
+	}
+}
+
+/// The operation ending an expression in reverse polish notation and the expressions of its operands
+fn operands(expression: &[Token]) -> Option<(Operator, Vec<&[Token]>)> {
+	let (Token::Operation(op), rest) = expression.split_last()?
+	else {
+		return None;
+	};
+	let arity = |token: &Token| match token {
+		Token::Operation(op) => match op.arity_and_types() {
+			ArityAndTypes::Unary(_) => 1,
+			ArityAndTypes::Binary(_, _) => 2,
+		},
+		_ => 0,
+	};
+	let mut operands = Vec::new();
+	let mut end = rest.len();
+	for _ in 0..arity(&Token::Operation(*op)) {
+		// walks back until the tokens make a single value
+		let mut start = end;
+		let mut needed = 1;
+		while needed > 0 {
+			start = start.checked_sub(1)?;
+			needed = needed - 1 + arity(&rest[start]);
+		}
+		operands.insert(0, &rest[start..end]);
+		end = start;
+	}
+	return Some((*op, operands));
+}
+
+/// Whether a test holds on every path reaching it, on some of them or on none
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Truth {
+	Always,
+	Sometimes,
+	Never,
+}
+
+/// The meta data of an object, with every value they can have on the paths reaching a point of the program
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct AbstractEnvironment {
+	pub assocs: Vec<(String, AbstractValue)>,
+}
+
+impl AbstractEnvironment {
+	pub fn new() -> AbstractEnvironment {
+		AbstractEnvironment { assocs: Vec::new() }
+	}
+
+	/// An environment holding the default values of the meta data
+	pub fn from_meta(meta_data: &[MetaData]) -> AbstractEnvironment {
+		let assocs = meta_data
+			.iter()
+			.map(|meta| (meta.name.clone(), AbstractValue::constant(&meta.data)))
+			.collect();
+		return AbstractEnvironment { assocs };
+	}
+
+	/// The values of `name`, unknown if it is not one of the meta data
+	pub fn fetch(&self, name: &str) -> AbstractValue {
+		for (n, value) in &self.assocs {
 			if n == name {
-				return id.clone();
+				return value.clone();
+			}
+		}
+		return AbstractValue::Unknown;
+	}
+
+	/// Each meta data can have any of the values it has in either environment
+	pub fn join(&self, other: &AbstractEnvironment) -> AbstractEnvironment {
+		let mut joined = self.clone();
+		for (name, value) in &mut joined.assocs {
+			if let Some((_, other)) = other.assocs.iter().find(|(other, _)| other == name) {
+				*value = value.join(other);
+			}
+		}
+		return joined;
+	}
+
+	/// Like `join`, but the bounds of the number meta data that move are dropped, so that following a loop ends.
+	/// Returns the names of the meta data widened
+	pub fn widen(&self, other: &AbstractEnvironment) -> (AbstractEnvironment, Vec<String>) {
+		let mut widened = Vec::new();
+		let mut joined = self.clone();
+		for (name, value) in &mut joined.assocs {
+			if let Some((_, other)) = other.assocs.iter().find(|(other, _)| other == name) {
+				let next = value.widen(other);
+				if next != value.join(other) {
+					widened.push(name.clone());
+				}
+				*value = next;
 			}
 		}
-		panic!("Variable {} not found", name);
+		return (joined, widened);
 	}
 
-	pub fn evaluate_rpn(&mut self, tokens: EvaluableExpr) -> Identifier {
+	/// Every value `expression` can evaluate to
+	pub fn evaluate(&self, expression: &EvaluableExpr) -> AbstractValue {
 		let mut stack = Vec::new();
-		for token in tokens {
+		for token in expression {
 			match token {
-				Token::Operand(operand) => stack.push(operand),
-				Token::Operation(op) => {
-					let arity_and_types = op.arity_and_types();
-					println!("op:{:?} stack:{:?} arity:{:?}", op, stack, arity_and_types);
-					match arity_and_types {
-						ArityAndTypes::Unary(_) => {
-							let operand = stack.pop().unwrap();
-							match (&op, &operand) {
-								(Operator::UnaryMinus, Identifier::Constant(Constant::Number(n))) => {
-									stack.push(Identifier::Constant(Constant::Number(-n)))
-								}
-								(Operator::Not, Identifier::Constant(Constant::Bool(b))) => {
-									stack.push(Identifier::Constant(Constant::Bool(!b)))
-								}
-								_ => panic!("Invalid unary operation {:?} {:?}", op, operand),
-							}
-						}
-						ArityAndTypes::Binary(_, _) => {
-							let mut operand2 = stack.pop().unwrap();
-							let mut operand1 = stack.pop().unwrap();
-
-							if let Identifier::Variable { name } = operand1 {
-								operand1 = self.fetch(&name);
-							}
-							if let Identifier::Variable { name } = operand2 {
-								operand2 = self.fetch(&name);
-							}
-
-							match (&op, &operand1, &operand2) {
-								(
-									Operator::Addition,
-									Identifier::Constant(Constant::Number(n1)),
-									Identifier::Constant(Constant::Number(n2)),
-								) => stack.push(Identifier::Constant(Constant::Number(n1 + n2))),
-								(
-									Operator::Substraction,
-									Identifier::Constant(Constant::Number(n1)),
-									Identifier::Constant(Constant::Number(n2)),
-								) => stack.push(Identifier::Constant(Constant::Number(n1 - n2))),
-								(
-									Operator::Multiplication,
-									Identifier::Constant(Constant::Number(n1)),
-									Identifier::Constant(Constant::Number(n2)),
-								) => stack.push(Identifier::Constant(Constant::Number(n1 * n2))),
-								(
-									Operator::Division,
-									Identifier::Constant(Constant::Number(n1)),
-									Identifier::Constant(Constant::Number(n2)),
-								) => stack.push(Identifier::Constant(Constant::Number(n1 / n2))),
-								(
-									Operator::Equals,
-									Identifier::Constant(Constant::Number(n1)),
-									Identifier::Constant(Constant::Number(n2)),
-								) => stack.push(Identifier::Constant(Constant::Bool(n1 == n2))),
-								(Operator::And, Identifier::Constant(Constant::Bool(b1)), Identifier::Constant(Constant::Bool(b2))) => {
-									stack.push(Identifier::Constant(Constant::Bool(*b1 && *b2)))
-								}
-								(Operator::Or, Identifier::Constant(Constant::Bool(b1)), Identifier::Constant(Constant::Bool(b2))) => {
-									stack.push(Identifier::Constant(Constant::Bool(*b1 || *b2)))
-								}
-
-								_ => panic!("Invalid binary operation {:?} {:?} {:?}", op, operand1, operand2),
-							}
-						}
+				Token::Operand(Identifier::Constant(constant)) => stack.push(AbstractValue::constant(constant)),
+				Token::Operand(Identifier::Variable { name }) => stack.push(self.fetch(name)),
+				Token::Operation(op) => match op.arity_and_types() {
+					ArityAndTypes::Unary(_) => {
+						let operand = stack.pop().unwrap();
+						stack.push(operand.unary(*op));
 					}
-				}
+					ArityAndTypes::Binary(_, _) => {
+						let operand2 = stack.pop().unwrap();
+						let operand1 = stack.pop().unwrap();
+						stack.push(operand1.binary(*op, &operand2));
+					}
+				},
 				_ => panic!("Invalid token {:?}", token),
 			}
 		}
 		return stack.pop().unwrap();
 	}
-}
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct Program {
-	pub checks: Vec<(ProgramVariable, Environment)>,
-}
+	pub fn evaluate_assignement(&mut self, assignement: Assignement) {
+		let result = self.evaluate(&assignement.value);
+		self.set(&assignement.name, result);
+	}
 
-impl Program {
-	pub fn from_vars(variables: Vec<ProgramVariable>) -> Program {
-		let mut v = Vec::new();
-		for variable in variables {
-			v.push((variable, Environment::new()));
+	pub fn set(&mut self, name: &str, value: AbstractValue) {
+		for i in 0..self.assocs.len() {
+			if self.assocs[i].0 == name {
+				self.assocs[i].1 = value;
+				return;
+			}
 		}
-		return Program { checks: v };
+		self.assocs.push((name.to_string(), value));
 	}
-}
 
-fn print_tokens(tokens: Vec<Token>) {
-	for token in tokens {
-		match token {
-			Token::Operand(Identifier::Constant(c)) => match c {
-				Constant::Bool(b) => print!("{} ", b),
-				Constant::Number(n) => print!("{} ", n),
-				Constant::String(s) => print!("{} ", s),
-			},
-			Token::Operand(Identifier::Variable { name }) => print!("{} ", name),
-			Token::Operation(op) => print!("{:?} ", op),
-			Token::Parenthesis(p) => print!("{:?} ", p),
+	/// Narrows the values so that `test` evaluates to `outcome`, as far as it compares variables to values:
+	/// `x`, `!x`, `x == 3`, `x < n` and the `&&` and `||` of those. What cannot be narrowed is left as it is
+	pub fn assume(&mut self, test: &[Token], outcome: bool) {
+		if let [Token::Operand(Identifier::Variable { name })] = test {
+			match self.fetch(name) {
+				AbstractValue::OneOf(values) if values.contains(&Constant::Bool(outcome)) => {
+					self.set(name, AbstractValue::OneOf(vec![Constant::Bool(outcome)]))
+				}
+				// a number is true when it is not 0
+				AbstractValue::Interval { .. } => {
+					let op = if outcome { Operator::NotEquals } else { Operator::Equals };
+					self.narrow(name, op, &AbstractValue::constant(&Constant::Number(0)));
+				}
+				_ => {}
+			}
+			return;
+		}
+		let Some((op, operands)) = operands(test)
+		else {
+			return;
+		};
+		match (op, outcome) {
+			(Operator::Not, _) => self.assume(operands[0], !outcome),
+			(Operator::And, true) | (Operator::Or, false) => {
+				self.assume(operands[0], outcome);
+				self.assume(operands[1], outcome);
+			}
+			(op, _) if op == Operator::Equals || op.is_comparison() => {
+				// the comparison that holds
+				let op = if outcome { op } else { op.negation() };
+				match (operands[0], operands[1]) {
+					([Token::Operand(Identifier::Variable { name })], other) => self.narrow(name, op, &self.evaluate(&other.to_vec())),
+					(other, [Token::Operand(Identifier::Variable { name })]) => {
+						self.narrow(name, op.mirror(), &self.evaluate(&other.to_vec()))
+					}
+					_ => {}
+				}
+			}
+			_ => {}
 		}
-		print!(" ");
 	}
-	println!();
+
+	/// Narrows the values of `name` to the ones for which `name op bound` holds
+	fn narrow(&mut self, name: &str, op: Operator, bound: &AbstractValue) {
+		let narrowed = match (self.fetch(name), bound) {
+			(AbstractValue::OneOf(values), AbstractValue::OneOf(bounds)) => {
+				let values = match (op, bounds.as_slice()) {
+					(Operator::Equals, _) => values.into_iter().filter(|value| bounds.contains(value)).collect(),
+					(Operator::NotEquals, [bound]) => values.into_iter().filter(|value| value != bound).collect(),
+					_ => return,
+				};
+				AbstractValue::OneOf(values)
+			}
+			(
+				AbstractValue::Interval { min, max },
+				AbstractValue::Interval {
+					min: bound_min,
+					max: bound_max,
+				},
+			) => {
+				// None is no bound, lower than any other for `max`
+				let highest = |a: Option<i32>, b: Option<i32>| a.max(b);
+				let lowest = |a: Option<i32>, b: Option<i32>| match (a, b) {
+					(Some(a), Some(b)) => Some(a.min(b)),
+					(a, b) => a.or(b),
+				};
+				let (min, max) = match op {
+					Operator::Equals => (highest(min, *bound_min), lowest(max, *bound_max)),
+					Operator::NotEquals if bound_min == bound_max && min == *bound_min => (min.and_then(|min| min.checked_add(1)), max),
+					Operator::NotEquals if bound_min == bound_max && max == *bound_max => (min, max.and_then(|max| max.checked_sub(1))),
+					Operator::Lower => (min, lowest(max, bound_max.and_then(|bound| bound.checked_sub(1)))),
+					Operator::LowerOrEqual => (min, lowest(max, *bound_max)),
+					Operator::Greater => (highest(min, bound_min.and_then(|bound| bound.checked_add(1))), max),
+					Operator::GreaterOrEqual => (highest(min, *bound_min), max),
+					_ => return,
+				};
+				AbstractValue::Interval { min, max }
+			}
+			_ => return,
+		};
+		// the test cannot hold, which is for the caller to know
+		let empty = match &narrowed {
+			AbstractValue::OneOf(values) => values.is_empty(),
+			AbstractValue::Interval { min, max } => min.zip(*max).is_some_and(|(min, max)| min > max),
+			AbstractValue::Unknown => false,
+		};
+		if !empty {
+			self.set(name, narrowed);
+		}
+	}
+
+	pub fn truth(&self, test: &EvaluableExpr) -> Truth {
+		match self.evaluate(test).bools() {
+			(true, false) => return Truth::Always,
+			(true, true) => return Truth::Sometimes,
+			_ => return Truth::Never,
+		}
+	}
+
+	/// The meta data as shown to the user
+	pub fn to_assocs(&self) -> Vec<(String, AbstractValue)> {
+		return self.assocs.clone();
+	}
 }
 
 #[cfg(test)]
 mod tests {
-	use crate::parser::{fill_blanks, parse_bracketed, Bracketed};
+	use crate::parser::{fill_blanks, parse_bracketed};
 
 	use super::*;
 
 	#[test]
 	fn test_tokenise() {
 		let expression = "!((a+5)==c)";
-		let tokens = Token::from_string(expression);
+		let tokens = Token::from_string(expression).unwrap();
 		assert_eq!(
 			tokens,
 			vec![
@@ -704,10 +1693,26 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn test_tokenise_comparisons() {
+		let tokens = Token::from_string("a!=1&&b<=2||!c").unwrap();
+		let operators = tokens
+			.iter()
+			.filter_map(|token| match token {
+				Token::Operation(op) => Some(*op),
+				_ => None,
+			})
+			.collect::<Vec<_>>();
+		use Operator::*;
+		assert_eq!(operators, vec![NotEquals, And, LowerOrEqual, Or, Not]);
+		let test = expr_tokens_to_rpn(Token::from_string("3 + 1 >= 4 && !(2 < 1) && 2 != 1").unwrap());
+		assert_eq!(AbstractEnvironment::new().truth(&test), Truth::Always);
+	}
+
 	#[test]
 	fn test_unary_minus_begin() {
 		let expression = "-5";
-		let tokens = Token::from_string(expression);
+		let tokens = Token::from_string(expression).unwrap();
 		assert_eq!(
 			tokens,
 			vec![Token::Operation(Operator::UnaryMinus), Token::Operand(Identifier::const_num(5)),]
@@ -717,7 +1722,7 @@ mod tests {
 	#[test]
 	fn test_unary_minus_in_expression() {
 		let expression = "5*-5";
-		let tokens = Token::from_string(expression);
+		let tokens = Token::from_string(expression).unwrap();
 		assert_eq!(
 			tokens,
 			vec![
@@ -732,7 +1737,7 @@ mod tests {
 	#[test]
 	fn test_tokenise_variables() {
 		let expression = "a + 5 + b";
-		let tokens = Token::from_string(expression);
+		let tokens = Token::from_string(expression).unwrap();
 		assert_eq!(
 			tokens,
 			vec![
@@ -770,15 +1775,15 @@ mod tests {
 	#[test]
 	fn test_evaluate() {
 		let expr = "5 + 3 * 2";
-		let tokens = Token::from_string(expr);
+		let tokens = Token::from_string(expr).unwrap();
 		let rpn = expr_tokens_to_rpn(tokens);
-		let result = evaluate_rpn(rpn);
-		assert_eq!(result, Identifier::const_num(11));
+		let result = AbstractEnvironment::new().evaluate(&rpn);
+		assert_eq!(result, AbstractValue::constant(&Constant::Number(11)));
 	}
 
 	#[test]
 	fn test_parse_meta_data() {
-		let meta_data = MetaData::from_string("name: number = 5");
+		let meta_data = MetaData::from_string("name: number = 5").unwrap();
 		assert_eq!(meta_data.name, "name");
 		assert_eq!(meta_data.data, Constant::Number(5));
 	}
@@ -786,14 +1791,16 @@ mod tests {
 	#[test]
 	fn test_parse_call() {
 		// let call = Call::fr("IntVector_push_back(&$self, ...) { Does { sorted = false } }");
-		let brack = parse_bracketed("IntVector_push_back(&$self, ...) {\n Does {\n sorted = false\n }\n }\n");
+		let brack = parse_bracketed("IntVector_push_back(&$self, ...) {\n Does {\n sorted = false\n }\n }\n").unwrap();
 		let mut brack = brack[0].clone();
 		println!("BRACK BEFORE {:?}", &brack);
 		brack = fill_blanks(&brack, "IntVector_push_back(&$self, ...) {\n Does {\n sorted = false\n }\n }\n");
 		println!("BRACK AFTER {:?}", &brack);
-		let call = Call::from_bracketed(brack);
+		let call = Call::from_bracketed(brack).unwrap();
 		assert_eq!(call.name, "IntVector_push_back(&$self, ...) {");
-		assert_eq!(call.does, Some(vec![Assignement::from_string("sorted = false")]));
+		assert_eq!(call.does, Some(vec![Assignement::from_string("sorted = false").unwrap()]));
+		assert_eq!(call.self_pattern(), Some((0, 1)));
+		assert_eq!(call.returns, None);
 
 		let warned = "        IntVector_search(&$self, ...) {
             Warn { 
@@ -804,11 +1811,11 @@ mod tests {
                 }
             }
         }";
-		let brack = parse_bracketed(warned);
+		let brack = parse_bracketed(warned).unwrap();
 		let mut brack = brack[0].clone();
 		brack = fill_blanks(&brack, warned);
 		println!("BRACK WARNED {:#?}", &brack);
-		let call = Call::from_bracketed(brack);
+		let call = Call::from_bracketed(brack).unwrap();
 		assert_eq!(call.name, "IntVector_search(&$self, ...) {");
 		assert_eq!(
 			call.warn,
@@ -822,13 +1829,130 @@ mod tests {
 				],
 				message: Some("Using search on an sorted vector is very inefficient".to_string()),
 				hint: Some("Use IntVector_binary_search(&$self, ...) instead".to_string()),
+				docs: None,
 			}])
 		);
 	}
 
+	#[test]
+	fn test_parse_returns() {
+		let guard = "Graph_build_index(&$self) {
+	Does {
+		built = true
+	}
+	Returns {
+		== 0: Does {
+			indexed = true
+		}
+		< 0: Does {
+			broken = true
+		}
+		else: Does {
+		}
+	}
+}";
+		let brack = fill_blanks(&parse_bracketed(guard).unwrap()[0], guard);
+		let call = Call::from_bracketed(brack).unwrap();
+		assert_eq!(call.does, Some(vec![Assignement::from_string("built = true").unwrap()]));
+		let returns = call.returns.as_ref().unwrap();
+		let tests = returns.iter().map(|outcome| outcome.returned).collect::<Vec<_>>();
+		assert_eq!(tests, vec![Some((Operator::Equals, 0)), Some((Operator::Lower, 0)), None]);
+		assert_eq!(returns[0].does, vec![Assignement::from_string("indexed = true").unwrap()]);
+		assert!(returns[2].does.is_empty());
+
+		let (indexed, broken, other) = (Some(&returns[0]), Some(&returns[1]), Some(&returns[2]));
+		assert_eq!(call.outcomes(None), vec![indexed, broken, other]);
+		assert_eq!(call.outcomes(Some((Operator::NotEquals, 0))), vec![broken, other]);
+		assert_eq!(call.outcomes(Some((Operator::Equals, 0))), vec![indexed]);
+		assert_eq!(call.outcomes(Some((Operator::GreaterOrEqual, 0))), vec![indexed, other]);
+		let mut call = call.clone();
+		call.returns.as_mut().unwrap().pop();
+		assert_eq!(
+			call.outcomes(Some((Operator::Greater, -1))),
+			vec![call.returns.as_ref().unwrap().first(), None]
+		);
+
+		// either of the outcomes written with the same test
+		let mut call = call.clone();
+		call.returns.as_mut().unwrap()[1].returned = Some((Operator::Equals, 0));
+		let returns = call.returns.as_ref().unwrap();
+		assert_eq!(
+			call.outcomes(Some((Operator::Equals, 0))),
+			vec![Some(&returns[0]), Some(&returns[1])]
+		);
+		assert_eq!(call.outcomes(Some((Operator::NotEquals, 0))), vec![None]);
+	}
+
+	#[test]
+	fn test_substitute() {
+		let test = Warning::from_string("Test: sorted == false && size > 0").unwrap().test;
+		let substituted = substitute(&test, &Assignement::from_string("sorted = !reversed").unwrap());
+		assert_eq!(
+			substituted,
+			Warning::from_string("Test: !reversed == false && size > 0").unwrap().test
+		);
+		let mut environment = AbstractEnvironment::new();
+		environment.set("reversed", AbstractValue::OneOf(vec![Constant::Bool(true)]));
+		environment.set("size", AbstractValue::Interval { min: Some(1), max: None });
+		assert_eq!(environment.truth(&substituted), Truth::Always);
+	}
+
+	#[test]
+	fn test_expression_to_string() {
+		for expression in [
+			"sorted == false && size > 0",
+			"!reversed == false",
+			"(a + b) * -c - (d - 2)",
+			"a - (b + c) == 4 || !(x && y)",
+			"name != \"vec\"",
+			"-3 + size",
+		] {
+			let test = Warning::from_string(&format!("Test: {}", expression)).unwrap().test;
+			assert_eq!(expression_to_string(&test), expression);
+		}
+		let assignement = Assignement::from_string("size = (size + 1) * 2").unwrap();
+		assert_eq!(assignement.to_guard(), "size = (size + 1) * 2");
+	}
+
+	#[test]
+	fn test_call_to_guard() {
+		let guard = "DataStruct IntVector {
+	Calls {
+		IntVector_find(&$self, x) {
+			Does {
+				searched = searched + 1
+			}
+			Returns {
+				== -1: Does {
+				}
+				else: Does {
+					found = true
+				}
+			}
+			Warn {
+				{
+					Test: sorted == false,
+					Message: \"vector is not sorted, searching it is slow\",
+					Hint: \"Sort it first\",
+					Docs: \"https://example.com/docs\"
+				}
+			}
+		}
+	}
+}";
+		let call = &DataStruct::parse_summary_file(guard).unwrap()[0].calls[0];
+		let written = format!(
+			"DataStruct IntVector {{\n\tCalls {{\n{}\t}}\n}}",
+			indented(&indented(&call.to_guard()))
+		);
+		assert_eq!(written, guard);
+		assert_eq!(call.meta_names(), vec!["searched", "found", "sorted"]);
+		assert_eq!(&DataStruct::parse_summary_file(&written).unwrap()[0].calls[0], call);
+	}
+
 	#[test]
 	fn test_parse_assignement() {
-		let assignement = Assignement::from_string("name = 5 + 3 * 2");
+		let assignement = Assignement::from_string("name = 5 + 3 * 2").unwrap();
 		assert_eq!(assignement.name, "name");
 		assert_eq!(
 			assignement.value,
@@ -845,7 +1969,8 @@ mod tests {
 	#[test]
 	fn test_parse_warning() {
 		let warning =
-			Warning::from_string("Test: a + 5 == c, Message: \"a + 5 should be equal to c\", Hint: \"Check the value of a and c\"");
+			Warning::from_string("Test: a + 5 == c, Message: \"a + 5 should be equal to c\", Hint: \"Check the value of a and c\"")
+				.unwrap();
 		assert_eq!(
 			warning.test,
 			vec![
@@ -858,11 +1983,20 @@ mod tests {
 		);
 		assert_eq!(warning.message, Some("a + 5 should be equal to c".to_string()));
 		assert_eq!(warning.hint, Some("Check the value of a and c".to_string()));
+		assert_eq!(warning.docs, None);
+	}
+
+	#[test]
+	fn test_parse_warning_docs() {
+		let warning = Warning::from_string("Message: \"Not: sorted\", Docs: \"https://example.com/vector#sort\"").unwrap();
+		assert_eq!(warning.test, vec![Token::Operand(Identifier::const_bool(true))]);
+		assert_eq!(warning.message, Some("Not: sorted".to_string()));
+		assert_eq!(warning.docs, Some("https://example.com/vector#sort".to_string()));
 	}
 
 	#[test]
 	fn test_parse_does() {
-		let does = Assignement::from_string("name = 5 + 3 * 2");
+		let does = Assignement::from_string("name = 5 + 3 * 2").unwrap();
 		assert!(does.name == "name");
 		assert_eq!(
 			does.value,
@@ -878,17 +2012,114 @@ mod tests {
 
 	#[test]
 	fn test_evaluate_env() {
-		let mut env = Environment::new();
+		let mut env = AbstractEnvironment::new();
 		let expr_1 = "a = 5";
-		let tokens_1 = Assignement::from_string(expr_1);
+		let tokens_1 = Assignement::from_string(expr_1).unwrap();
 		let expr_2 = "b = 3";
-		let tokens_2 = Assignement::from_string(expr_2);
+		let tokens_2 = Assignement::from_string(expr_2).unwrap();
 		let expr_3 = "a == (b + 2)";
-		let tokens_3 = Token::from_string(expr_3);
+		let tokens_3 = Token::from_string(expr_3).unwrap();
 		let rpn = expr_tokens_to_rpn(tokens_3);
 		env.evaluate_assignement(tokens_1);
 		env.evaluate_assignement(tokens_2);
-		let result = env.evaluate_rpn(rpn);
-		assert_eq!(result, Identifier::const_bool(true));
+		assert_eq!(env.truth(&rpn), Truth::Always);
+	}
+
+	#[test]
+	fn test_abstract_env() {
+		let meta = vec![
+			MetaData::from_string("sorted: bool = true").unwrap(),
+			MetaData::from_string("size: number = 0").unwrap(),
+		];
+		let mut grown = AbstractEnvironment::from_meta(&meta);
+		grown.evaluate_assignement(Assignement::from_string("sorted = false").unwrap());
+		grown.evaluate_assignement(Assignement::from_string("size = size + 1").unwrap());
+		let joined = AbstractEnvironment::from_meta(&meta).join(&grown);
+		assert_eq!(
+			joined.fetch("sorted"),
+			AbstractValue::OneOf(vec![Constant::Bool(true), Constant::Bool(false)])
+		);
+		assert_eq!(joined.fetch("size").to_string(), "[0, 1]");
+		let test = |expression: &str| expr_tokens_to_rpn(Token::from_string(expression).unwrap());
+		assert_eq!(joined.truth(&test("sorted == false")), Truth::Sometimes);
+		assert_eq!(joined.truth(&test("size == 2")), Truth::Never);
+		assert_eq!(joined.truth(&test("size <= 1 && !(size < 0)")), Truth::Always);
+		assert_eq!(joined.truth(&test("size * 2 >= 2")), Truth::Sometimes);
+		assert_eq!(joined.truth(&test("size != 0 || sorted")), Truth::Sometimes);
+		assert_eq!(grown.truth(&test("size == 1")), Truth::Always);
+
+		let (widened, names) = AbstractEnvironment::from_meta(&meta).widen(&grown);
+		assert_eq!(names, vec!["size".to_string()]);
+		assert_eq!(widened.fetch("size").to_string(), "[0, +inf]");
+		assert_eq!(widened.truth(&test("size > -1")), Truth::Always);
+		assert_eq!(widened.truth(&test("size - 5 == 0")), Truth::Sometimes);
+		assert_eq!(widened.truth(&test("sorted == false")), Truth::Sometimes);
+		assert_eq!(grown.widen(&grown), (grown.clone(), Vec::new()));
+	}
+
+	#[test]
+	fn test_assume() {
+		let test = |expression: &str| expr_tokens_to_rpn(Token::from_string(expression).unwrap());
+		let mut environment = AbstractEnvironment::new();
+		environment.set("done", AbstractValue::from_bools(true, true));
+		environment.set("n", AbstractValue::Interval { min: None, max: None });
+		let mut assumed = environment.clone();
+		assumed.assume(&test("!done && n >= 2"), true);
+		assert_eq!(assumed.fetch("done"), AbstractValue::constant(&Constant::Bool(false)));
+		assert_eq!(assumed.fetch("n").to_string(), "[2, +inf]");
+		assumed.assume(&test("5 > n"), true);
+		assert_eq!(assumed.fetch("n").to_string(), "[2, 4]");
+		assumed.assume(&test("n != 2"), true);
+		assert_eq!(assumed.truth(&test("n == 2 || done")), Truth::Never);
+		assumed.assume(&test("n"), true);
+		assert_eq!(assumed.fetch("n").to_string(), "[3, 4]");
+
+		// nothing is known of either side of a failed `&&`
+		let mut assumed = environment.clone();
+		assumed.assume(&test("done && n == 1"), false);
+		assert_eq!(assumed, environment);
+		assumed.assume(&test("n"), false);
+		assert_eq!(assumed.truth(&test("n")), Truth::Never);
+		assert_eq!(assumed.truth(&test("!n && n + 1 > 0")), Truth::Always);
+	}
+
+	#[test]
+	fn test_parse_no_copy() {
+		let guards = DataStruct::parse_guards("DataStruct IntVector NoCopy {\n\tMeta {\n\t\tsorted: bool = true\n\t}\n}\n").unwrap();
+		assert_eq!(guards[0].name, "IntVector");
+		assert!(guards[0].no_copy);
+		let guards = DataStruct::parse_guards("DataStruct IntVector {\n\tMeta {\n\t\tsorted: bool = true\n\t}\n}\n").unwrap();
+		assert!(!guards[0].no_copy);
+	}
+
+	#[test]
+	fn test_parse_handle() {
+		let handle = |lines: &[&str]| {
+			let lines = lines.iter().map(|line| line.to_string()).collect::<Vec<_>>();
+			return Handle::from_lines("Graph", &lines).unwrap();
+		};
+		let value = handle(&[]);
+		assert_eq!(
+			(value.kind, value.type_name.as_str(), value.pointer_depth, value.nullable),
+			(HandleKind::Value, "Graph", 0, false)
+		);
+		let pointer = handle(&["Kind: pointer"]);
+		assert_eq!(
+			(pointer.kind, pointer.type_name.as_str(), pointer.pointer_depth, pointer.nullable),
+			(HandleKind::Pointer, "Graph", 1, true)
+		);
+		let opaque = handle(&["Kind: pointer", "Type: GraphHandle", "Nullable: false"]);
+		assert_eq!(
+			(opaque.type_name.as_str(), opaque.pointer_depth, opaque.nullable),
+			("GraphHandle", 0, false)
+		);
+		let integer = handle(&["Kind: integer"]);
+		assert_eq!(
+			(integer.kind, integer.type_name.as_str(), integer.pointer_depth),
+			(HandleKind::Integer, "int", 0)
+		);
+		assert_eq!((value.escapes, handle(&["Escapes: warn"]).escapes), (None, Some(Escape::Warn)));
+		let typo = Handle::from_lines("Graph", &["Nullable: ture".to_string()]);
+		assert_eq!(typo, Err("Invalid Nullable value: \"ture\"".to_string()));
 	}
 }
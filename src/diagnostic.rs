@@ -0,0 +1,168 @@
+use crate::data_struct::AbstractValue;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+pub enum Severity {
+	/// Only used for the rules broken on some paths, when asked for
+	Note,
+	Warning,
+	Error,
+}
+
+impl Severity {
+	pub fn from_string(severity: &str) -> Result<Severity, String> {
+		match severity {
+			"note" => Ok(Severity::Note),
+			"warning" => Ok(Severity::Warning),
+			"error" => Ok(Severity::Error),
+			_ => Err(format!("Unknown severity: {}", severity)),
+		}
+	}
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Severity::Note => "note",
+			Severity::Warning => "warning",
+			Severity::Error => "error",
+		}
+	}
+}
+
+/// A span of a source file, lines and columns start at 1
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+	pub file: String,
+	pub line: usize,
+	pub column: usize,
+	pub end_line: usize,
+	pub end_column: usize,
+	pub start_byte: usize,
+	pub end_byte: usize,
+}
+
+impl Location {
+	/// The location of the bytes `start..end` of `source_code`
+	pub fn from_bytes(file: &str, source_code: &str, start: usize, end: usize) -> Location {
+		let position = |byte: usize| {
+			let before = &source_code[..byte];
+			let line = before.matches('\n').count() + 1;
+			let column = byte - before.rfind('\n').map_or(0, |newline| newline + 1) + 1;
+			return (line, column);
+		};
+		let (line, column) = position(start);
+		let (end_line, end_column) = position(end);
+		return Location {
+			file: file.to_string(),
+			line,
+			column,
+			end_line,
+			end_column,
+			start_byte: start,
+			end_byte: end,
+		};
+	}
+}
+
+/// A place related to a diagnostic, e.g. the call that last changed the meta data it tested
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelatedLocation {
+	pub location: Location,
+	pub message: String,
+}
+
+/// A rule of a guard, one per `Warn` or `Denies` entry of a call
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+	pub id: String,
+	pub data_struct: String,
+	/// The guarded call, e.g. `IntVector_search(&$self, ...)`
+	pub call: String,
+	pub severity: Severity,
+	pub message: String,
+	pub hint: Option<String>,
+	pub docs: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+	/// Stable identifier of the guard rule, e.g. `IntVector.IntVector_search.warn0`
+	pub rule_id: String,
+	pub severity: Severity,
+	pub message: String,
+	pub hint: Option<String>,
+	pub data_struct: String,
+	/// The tracked variable bound to `$self`, if the call has one
+	pub variable: Option<String>,
+	/// Values of the meta data just before the call
+	pub meta: Vec<(String, AbstractValue)>,
+	pub location: Location,
+	/// Where the meta data used by the rule got its value
+	pub related: Vec<RelatedLocation>,
+	/// The lines run to get to the location, when the paths are followed one by one
+	pub path: Vec<usize>,
+}
+
+/// The meta data of a tracked variable after a call changed it
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateChange {
+	pub location: Location,
+	/// The call as written in the source, e.g. `IntVector_sort(&vec)`
+	pub call: String,
+	pub meta: Vec<(String, AbstractValue)>,
+}
+
+/// How the meta data of a tracked variable evolved through the program
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timeline {
+	pub variable: String,
+	pub data_struct: String,
+	pub declaration: Location,
+	/// Values of the meta data at the declaration
+	pub initial: Vec<(String, AbstractValue)>,
+	pub changes: Vec<StateChange>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceFile {
+	pub path: String,
+	pub content: String,
+}
+
+/// Everything produced by a checking run
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Report {
+	pub sources: Vec<SourceFile>,
+	pub rules: Vec<Rule>,
+	pub diagnostics: Vec<Diagnostic>,
+	pub timelines: Vec<Timeline>,
+}
+
+impl Report {
+	pub fn new() -> Report {
+		Report {
+			sources: Vec::new(),
+			rules: Vec::new(),
+			diagnostics: Vec::new(),
+			timelines: Vec::new(),
+		}
+	}
+
+	/// Adds the findings of another run, e.g. of another source file
+	pub fn extend(&mut self, other: Report) {
+		self.sources.extend(other.sources);
+		self.diagnostics.extend(other.diagnostics);
+		self.timelines.extend(other.timelines);
+		for rule in other.rules {
+			if !self.rules.iter().any(|r| r.id == rule.id) {
+				self.rules.push(rule);
+			}
+		}
+	}
+
+	pub fn source(&self, path: &str) -> Option<&SourceFile> {
+		return self.sources.iter().find(|source| source.path == path);
+	}
+
+	pub fn has_errors(&self) -> bool {
+		return self.diagnostics.iter().any(|diagnostic| diagnostic.severity == Severity::Error);
+	}
+}
@@ -0,0 +1,106 @@
+/// A JSON value, enough to write the reports without pulling a serialisation library
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+	Null,
+	Bool(bool),
+	Number(i64),
+	String(String),
+	Array(Vec<Json>),
+	Object(Vec<(String, Json)>),
+}
+
+impl Json {
+	pub fn str(s: &str) -> Json {
+		Json::String(s.to_string())
+	}
+
+	pub fn optional_str(s: &Option<String>) -> Json {
+		match s {
+			Some(s) => Json::String(s.clone()),
+			None => Json::Null,
+		}
+	}
+
+	pub fn object(fields: Vec<(&str, Json)>) -> Json {
+		Json::Object(fields.into_iter().map(|(name, value)| (name.to_string(), value)).collect())
+	}
+
+	/// Pretty printed with tabs, the way the rest of the project is indented
+	pub fn to_string_pretty(&self) -> String {
+		let mut output = String::new();
+		self.write(&mut output, 0);
+		output.push('\n');
+		return output;
+	}
+
+	fn write(&self, output: &mut String, depth: usize) {
+		let indent = "\t".repeat(depth + 1);
+		match self {
+			Json::Null => output.push_str("null"),
+			Json::Bool(b) => output.push_str(&b.to_string()),
+			Json::Number(n) => output.push_str(&n.to_string()),
+			Json::String(s) => output.push_str(&escape(s)),
+			Json::Array(values) if values.is_empty() => output.push_str("[]"),
+			Json::Object(fields) if fields.is_empty() => output.push_str("{}"),
+			Json::Array(values) => {
+				output.push_str("[\n");
+				for (i, value) in values.iter().enumerate() {
+					output.push_str(&indent);
+					value.write(output, depth + 1);
+					output.push_str(if i + 1 < values.len() { ",\n" } else { "\n" });
+				}
+				output.push_str(&"\t".repeat(depth));
+				output.push(']');
+			}
+			Json::Object(fields) => {
+				output.push_str("{\n");
+				for (i, (name, value)) in fields.iter().enumerate() {
+					output.push_str(&indent);
+					output.push_str(&escape(name));
+					output.push_str(": ");
+					value.write(output, depth + 1);
+					output.push_str(if i + 1 < fields.len() { ",\n" } else { "\n" });
+				}
+				output.push_str(&"\t".repeat(depth));
+				output.push('}');
+			}
+		}
+	}
+}
+
+pub fn escape(s: &str) -> String {
+	let mut escaped = String::from("\"");
+	for c in s.chars() {
+		match c {
+			'"' => escaped.push_str("\\\""),
+			'\\' => escaped.push_str("\\\\"),
+			'\n' => escaped.push_str("\\n"),
+			'\r' => escaped.push_str("\\r"),
+			'\t' => escaped.push_str("\\t"),
+			c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+			c => escaped.push(c),
+		}
+	}
+	escaped.push('"');
+	return escaped;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_escape() {
+		assert_eq!(escape("a \"b\"\n\\"), "\"a \\\"b\\\"\\n\\\\\"");
+		assert_eq!(escape("\u{1}"), "\"\\u0001\"");
+	}
+
+	#[test]
+	fn test_pretty() {
+		let json = Json::object(vec![
+			("a", Json::Array(vec![Json::Number(1), Json::Null])),
+			("b", Json::Object(Vec::new())),
+		]);
+		assert_eq!(json.to_string_pretty(), "{\n\t\"a\": [\n\t\t1,\n\t\tnull\n\t],\n\t\"b\": {}\n}\n");
+	}
+}
@@ -0,0 +1,328 @@
+//! TinyGuard checks that C code uses a library the way its authors intended,
+//! following the rules they wrote in guard files (`.tngd`).
+//!
+//! ```no_run
+//! let mut tiny_guard = tiny_guard::TinyGuard::new();
+//! tiny_guard.load_guards_from_path("samples/vector.tngd").unwrap();
+//! for diagnostic in tiny_guard.analyse_file("samples/main.c").unwrap() {
+//!     eprintln!("{}:{}: {}", diagnostic.location.file, diagnostic.location.line, diagnostic.message);
+//! }
+//! ```
+//!
+//! Nothing is printed by the library, debugging messages go through [`logger`] and are off by default.
+
+#![allow(clippy::needless_return)]
+
+pub mod ast;
+pub mod cfg;
+pub mod checker;
+pub mod data_struct;
+pub mod diagnostic;
+pub mod json;
+pub mod logger;
+pub mod output;
+pub mod parser;
+pub mod summary;
+
+use std::{fmt, io, path::Path};
+
+pub use checker::Options;
+pub use data_struct::DataStruct;
+pub use diagnostic::{Diagnostic, Location, RelatedLocation, Report, Rule, Severity};
+pub use output::OutputFormat;
+pub use summary::Summary;
+
+/// Why a guard or summary file could not be loaded
+#[derive(Debug)]
+pub enum Error {
+	Io(io::Error),
+	/// The file is malformed, e.g. `line 3: Unknown handle kind: "list"`
+	Parse(String),
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Error::Io(error) => write!(f, "{}", error),
+			Error::Parse(message) => write!(f, "{}", message),
+		}
+	}
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+	fn from(error: io::Error) -> Error {
+		return Error::Io(error);
+	}
+}
+
+/// A set of guards to check C sources against
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TinyGuard {
+	guards: Vec<DataStruct>,
+	options: Options,
+	/// What the functions of the other files of the program do
+	summaries: Vec<Summary>,
+}
+
+impl TinyGuard {
+	pub fn new() -> TinyGuard {
+		TinyGuard {
+			guards: Vec::new(),
+			options: Options::default(),
+			summaries: Vec::new(),
+		}
+	}
+
+	/// Adds the DataStructs of a guard file given as a string, none if it is malformed
+	pub fn load_guards(&mut self, guard_file: &str) -> Result<(), Error> {
+		self.guards.extend(DataStruct::parse_guards(guard_file).map_err(Error::Parse)?);
+		return Ok(());
+	}
+
+	/// Adds the DataStructs of the guard file at `path`
+	pub fn load_guards_from_path(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+		let guard_file = std::fs::read_to_string(path)?;
+		return self.load_guards(&guard_file);
+	}
+
+	pub fn guards(&self) -> &[DataStruct] {
+		return &self.guards;
+	}
+
+	/// Adds the summaries of a summary file given as a string, see [`summary::summaries_to_string`],
+	/// none if it is malformed
+	pub fn load_summaries(&mut self, summary_file: &str) -> Result<(), Error> {
+		self.summaries.extend(summary::parse_summaries(summary_file).map_err(Error::Parse)?);
+		return Ok(());
+	}
+
+	/// Adds the summaries of the summary file at `path`
+	pub fn load_summaries_from_path(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+		let summary_file = std::fs::read_to_string(path)?;
+		return self.load_summaries(&summary_file);
+	}
+
+	pub fn summaries(&self) -> &[Summary] {
+		return &self.summaries;
+	}
+
+	/// Summarises the functions of the C sources (file, source code) of a program that other files can call,
+	/// each source knowing the summaries of the others and the loaded ones.
+	/// The summaries are kept for the sources checked next and returned to be saved
+	pub fn link_sources(&mut self, sources: &[(&str, &str)]) -> Vec<Summary> {
+		let mut linked = vec![Vec::new(); sources.len()];
+		// summarised again while the summaries of the files they call change
+		for _ in 0..summary::MAX_ROUNDS {
+			let next = sources
+				.iter()
+				.enumerate()
+				.map(|(i, (file, source_code))| {
+					let others = linked
+						.iter()
+						.enumerate()
+						.filter(|(j, _)| *j != i)
+						.flat_map(|(_, summaries)| summaries);
+					let known = others.chain(&self.summaries).cloned().collect::<Vec<_>>();
+					return summary::summarise_source(&self.guards, &known, &self.options, file, source_code);
+				})
+				.collect::<Vec<_>>();
+			if next == linked {
+				break;
+			}
+			linked = next;
+		}
+		let linked = linked.concat();
+		// the summaries of the sources come before the loaded ones, which may be older
+		self.summaries.splice(0..0, linked.iter().cloned());
+		return linked;
+	}
+
+	pub fn options(&self) -> &Options {
+		return &self.options;
+	}
+
+	/// Turns on or off the checks that are not written in the guards
+	pub fn set_options(&mut self, options: Options) {
+		self.options = options;
+	}
+
+	/// Every rule of the loaded guards
+	pub fn rules(&self) -> Vec<Rule> {
+		return checker::guard_rules(&self.guards, &self.options);
+	}
+
+	/// Checks a C source, `file` is only used to name it in the diagnostics
+	pub fn analyse_source(&self, file: &str, source_code: &str) -> Vec<Diagnostic> {
+		return self.report_source(file, source_code).diagnostics;
+	}
+
+	/// Checks the C source at `path`
+	pub fn analyse_file(&self, path: impl AsRef<Path>) -> io::Result<Vec<Diagnostic>> {
+		return Ok(self.report_file(path)?.diagnostics);
+	}
+
+	/// Like [`TinyGuard::analyse_source`], with everything needed to render the results with [`output::render`]
+	pub fn report_source(&self, file: &str, source_code: &str) -> Report {
+		let mut report = Report::new();
+		report.rules = self.rules();
+		report.extend(checker::check_linked_source(
+			&self.guards,
+			&self.summaries,
+			&self.options,
+			file,
+			source_code,
+		));
+		return report;
+	}
+
+	/// Like [`TinyGuard::analyse_file`], with everything needed to render the results with [`output::render`]
+	pub fn report_file(&self, path: impl AsRef<Path>) -> io::Result<Report> {
+		let path = path.as_ref();
+		let source_code = std::fs::read_to_string(path)?;
+		return Ok(self.report_source(&path.to_string_lossy(), &source_code));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const GUARD: &str = "DataStruct IntVector {
+	Meta {
+		sorted: bool = true
+	}
+	Calls {
+		IntVector_push_back(&$self, ...) {
+			Does {
+				sorted = false
+			}
+		}
+		IntVector_binary_search(&$self, ...) {
+			Denies {
+				{
+					Test: sorted == false,
+					Message: \"vector is not sorted\"
+				}
+			}
+		}
+	}
+}";
+
+	#[test]
+	fn test_analyse_source() {
+		let mut tiny_guard = TinyGuard::new();
+		tiny_guard.load_guards(GUARD).unwrap();
+		assert_eq!(tiny_guard.guards().len(), 1);
+		assert_eq!(tiny_guard.rules().len(), 1);
+		let diagnostics = tiny_guard.analyse_source(
+			"main.c",
+			"int main() {
+	IntVector vec;
+	IntVector_push_back(&vec, 3);
+	IntVector_binary_search(&vec, 3);
+}",
+		);
+		assert_eq!(diagnostics.len(), 1);
+		assert_eq!(diagnostics[0].message, "vector is not sorted");
+		assert_eq!(diagnostics[0].location.file, "main.c");
+	}
+
+	#[test]
+	fn test_missing_file() {
+		let tiny_guard = TinyGuard::new();
+		assert!(tiny_guard.analyse_file("does/not/exist.c").is_err());
+		let mut tiny_guard = TinyGuard::new();
+		assert!(tiny_guard.load_guards_from_path("does/not/exist.tngd").is_err());
+	}
+
+	#[test]
+	fn test_malformed_file() {
+		let mut tiny_guard = TinyGuard::new();
+		let error = tiny_guard
+			.load_guards(&GUARD.replace("sorted = false", "sorted false"))
+			.unwrap_err();
+		assert_eq!(
+			error.to_string(),
+			"line 7: Expected `name = value` in Does, found: \"sorted false\""
+		);
+		let error = tiny_guard
+			.load_guards(&GUARD.replace("Meta {", "Handle {\n\t\tKind: list\n\t}\n\tMeta {"))
+			.unwrap_err();
+		assert_eq!(error.to_string(), "line 2: Unknown handle kind: \"list\"");
+		let error = tiny_guard.load_guards(GUARD.trim_end_matches('}')).unwrap_err();
+		assert_eq!(error.to_string(), "line 1: `{` is never closed");
+		let error = tiny_guard.load_guards(&GUARD.replace("Test: sorted", "Test: sortd")).unwrap_err();
+		assert_eq!(error.to_string(), "line 11: Unknown meta data \"sortd\"");
+		let error = tiny_guard.load_summaries("DataStruct IntVector {\n\tCalls {\n\t\tprepare(&$self) {\n\t\t\tReturns {\n\t\t\t\t< zero: Does {\n\t\t\t\t}\n\t\t\t}\n\t\t}\n\t}\n}\n").unwrap_err();
+		assert_eq!(error.to_string(), "line 5: Expected a number in Returns, found: \"zero\"");
+		// nothing is added from a malformed file
+		assert!(tiny_guard.guards().is_empty());
+		assert!(tiny_guard.summaries().is_empty());
+	}
+
+	#[test]
+	fn test_link_sources() {
+		let util = "void prepare(IntVector* v) {
+	IntVector_binary_search(v, 0);
+	IntVector_push_back(v, 1);
+}";
+		let main = "int main() {
+	IntVector vec;
+	IntVector_push_back(&vec, 3);
+	prepare(&vec);
+	IntVector_binary_search(&vec, 3);
+}";
+		// on its own, `main` knows nothing of `prepare`
+		let mut tiny_guard = TinyGuard::new();
+		tiny_guard.load_guards(GUARD).unwrap();
+		assert_eq!(tiny_guard.analyse_source("main.c", main).len(), 1);
+
+		let summaries = tiny_guard.link_sources(&[("main.c", main), ("util.c", util)]);
+		assert_eq!(tiny_guard.summaries(), summaries);
+		// checked for nothing, but still part of the report
+		let report = tiny_guard.report_source("util.c", util);
+		assert!(report.diagnostics.is_empty());
+		assert_eq!(
+			report.sources.iter().map(|source| source.path.as_str()).collect::<Vec<_>>(),
+			vec!["util.c"]
+		);
+		let lines = |diagnostics: Vec<Diagnostic>| diagnostics.iter().map(|d| (d.location.line, d.message.clone())).collect::<Vec<_>>();
+		let expected = vec![
+			(4, "vector is not sorted (in `prepare`)".to_string()),
+			(5, "vector is not sorted".to_string()),
+		];
+		assert_eq!(lines(tiny_guard.analyse_source("main.c", main)), expected);
+
+		// the same through a summary file
+		let mut linked = TinyGuard::new();
+		linked.load_guards(GUARD).unwrap();
+		linked.load_summaries(&summary::summaries_to_string(&summaries)).unwrap();
+		assert_eq!(lines(linked.analyse_source("main.c", main)), expected);
+	}
+
+	#[test]
+	fn test_summary_rules() {
+		let mut tiny_guard = TinyGuard::new();
+		tiny_guard.load_guards(GUARD).unwrap();
+		let report = tiny_guard.report_source(
+			"main.c",
+			"void lookup(IntVector* v) {
+	IntVector_binary_search(v, 3);
+}
+int main() {
+	IntVector vec;
+	IntVector_push_back(&vec, 3);
+	lookup(&vec);
+}",
+		);
+		assert_eq!(report.diagnostics.len(), 1);
+		assert_eq!(report.diagnostics[0].rule_id, "IntVector.lookup.deny0");
+		let junit = output::render(OutputFormat::Junit, &report);
+		assert!(junit.contains("<testsuites name=\"TinyGuard\" tests=\"2\" failures=\"1\" errors=\"0\">"));
+		assert!(junit.contains("<failure type=\"error\" message=\"vector is not sorted (in `lookup`) (1 time(s))\">"));
+		let sarif = output::render(OutputFormat::Sarif, &report);
+		assert!(sarif.contains("\"ruleIndex\": 1"));
+	}
+}
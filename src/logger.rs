@@ -0,0 +1,65 @@
+use std::{
+	fmt::Arguments,
+	sync::{
+		atomic::{AtomicU8, Ordering},
+		RwLock,
+	},
+};
+
+/// How much TinyGuard tells about what it is doing, nothing by default
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+pub enum Level {
+	Off = 0,
+	/// What a user might want to know, e.g. when the analysis had to approximate
+	Verbose = 1,
+	/// Everything, only useful to work on TinyGuard itself
+	Debug = 2,
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Off as u8);
+
+/// Receives every message that is logged
+pub type Sink = fn(Level, &str);
+
+static SINK: RwLock<Option<Sink>> = RwLock::new(None);
+
+pub fn set_level(level: Level) {
+	LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn enabled(level: Level) -> bool {
+	return level != Level::Off && level as u8 <= LEVEL.load(Ordering::Relaxed);
+}
+
+/// Where the messages go, the standard error if no sink is set
+pub fn set_sink(sink: Sink) {
+	*SINK.write().unwrap() = Some(sink);
+}
+
+pub fn write(level: Level, arguments: Arguments) {
+	let message = arguments.to_string();
+	match *SINK.read().unwrap() {
+		Some(sink) => sink(level, &message),
+		None => eprintln!("{}", message),
+	}
+}
+
+/// Logs a message when the verbose output is on
+#[macro_export]
+macro_rules! verbose {
+	($($arg:tt)*) => {
+		if $crate::logger::enabled($crate::logger::Level::Verbose) {
+			$crate::logger::write($crate::logger::Level::Verbose, format_args!($($arg)*));
+		}
+	};
+}
+
+/// Logs a message when the debug output is on
+#[macro_export]
+macro_rules! debug {
+	($($arg:tt)*) => {
+		if $crate::logger::enabled($crate::logger::Level::Debug) {
+			$crate::logger::write($crate::logger::Level::Debug, format_args!($($arg)*));
+		}
+	};
+}
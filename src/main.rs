@@ -1,52 +1,187 @@
-mod ast;
-mod data_struct;
-mod parser;
-
-use std::fs::File;
-use std::io::Read;
-
-use ast::entry_point_c;
-use data_struct::Program;
-use parser::parse_bracketed;
-fn read_file(file_path: &str) -> String {
-	let mut file = File::open(file_path).expect("File not found");
-	let mut contents = String::new();
-	file.read_to_string(&mut contents).expect("Failed to read file");
-	return contents;
+#![allow(clippy::needless_return)]
+
+use std::process::ExitCode;
+
+use tiny_guard::{ast, data_struct::Escape, debug, logger, output, summary, Options, OutputFormat, Report, Severity, TinyGuard};
+
+const USAGE: &str = "Usage: tiny_guard [OPTIONS] --guard <GUARD> <SOURCE>...
+
+Options:
+  -g, --guard <GUARD>     Guard file (.tngd) to check the sources against, can be repeated
+  -f, --format <FORMAT>   Output format: human, gcc, json, sarif, html, junit or checkstyle
+                          [default: human]
+  -o, --output <FILE>     Write the report to FILE instead of the standard output
+      --no-use-after-destroy
+                          Do not report the DataStructs used or destroyed again after being destroyed
+      --no-leaks          Do not report the DataStructs that are never destroyed
+      --may-severity <SEVERITY>
+                          Severity of the rules broken on some paths only: note, warning, error or off
+                          [default: warning]
+      --entry <FUNCTION>  Function to check on its own instead of `main`, `*` and `?` match any characters
+                          and any character (`test_*`), can be repeated
+      --all-functions     Check every function on its own, the same as --entry '*'
+      --path-sensitive    Follow the paths of the entry points one by one, the branches testing the same
+                          local variables agree and each diagnostic comes with the lines taken to reach it
+      --path-budget <N>   Most paths followed one by one, past it they are joined [default: 1000]
+      --noreturn <FUNCTION>
+                          Function that never returns, on top of `exit`, `abort`, `longjmp` and the ones
+                          marked `_Noreturn`, can be repeated
+      --escape <POLICY>   What a function no guard or summary describes does to the DataStructs given to it:
+                          havoc (their meta data can be anything), keep or warn [default: havoc]
+      --summaries <FILE>  Summaries of the functions of other files of the program, written with
+                          --write-summaries, can be repeated
+      --write-summaries <FILE>
+                          Only summarise the functions of the sources, for the files calling them,
+                          and write the summaries to FILE
+  -v, --verbose           Tell when the analysis had to make approximations
+      --debug             Print everything about the parsing and the analysis
+  -h, --help              Print this help";
+
+struct Arguments {
+	guards: Vec<String>,
+	sources: Vec<String>,
+	format: OutputFormat,
+	output: Option<String>,
+	options: Options,
+	entry_points: Vec<String>,
+	summaries: Vec<String>,
+	write_summaries: Option<String>,
+	log_level: logger::Level,
 }
 
-fn main() {
-	let code = read_file("samples/main.c");
-	let guard_file = read_file("samples/vector.tngd");
-	let brack = parse_bracketed(&guard_file);
-	let brack = brack
-		.iter()
-		.map(|brack| parser::fill_blanks(brack, &guard_file))
-		.collect::<Vec<_>>();
-	println!("BRACK : {:#?}", brack);
-
-	let tree = ast::parse_ast("samples/main.c");
-	ast::print_tree(tree.root_node(), &code, 0);
-	let variables = ast::parse_variables(&tree, &code);
-	for variable in &variables {
-		ast::print_variables(variable, &code);
-	}
-	let entry_point = entry_point_c(&tree, &code);
-	println!(
-		"ENTRY POINT: {:?}, {}",
-		entry_point,
-		code[entry_point.start_byte()..entry_point.end_byte()].to_string()
-	);
-
-	for variable in &variables {
-		println!("variable: {}", variable.name);
-		for impli in &variable.implications {
-			if impli.0 >= entry_point.start_byte() && entry_point.end_byte() >= impli.1 {
-				println!("impli: {}", code[impli.0..impli.1].to_string());
+impl Arguments {
+	fn parse(args: &[String]) -> Result<Arguments, String> {
+		let mut arguments = Arguments {
+			guards: Vec::new(),
+			sources: Vec::new(),
+			format: OutputFormat::Human,
+			output: None,
+			options: Options::default(),
+			entry_points: Vec::new(),
+			summaries: Vec::new(),
+			write_summaries: None,
+			log_level: logger::Level::Off,
+		};
+		let mut args = args.iter();
+		while let Some(arg) = args.next() {
+			let mut value = |option: &str| args.next().cloned().ok_or(format!("Missing value for {}", option));
+			match arg.as_str() {
+				"-g" | "--guard" => arguments.guards.push(value(arg)?),
+				"-f" | "--format" => arguments.format = OutputFormat::from_string(&value(arg)?)?,
+				"-o" | "--output" => arguments.output = Some(value(arg)?),
+				"--no-use-after-destroy" => arguments.options.use_after_destroy = false,
+				"--no-leaks" => arguments.options.leaks = false,
+				"--may-severity" => {
+					arguments.options.may_severity = match value(arg)?.as_str() {
+						"off" => None,
+						severity => Some(Severity::from_string(severity)?),
+					}
+				}
+				"--entry" => arguments.entry_points.push(value(arg)?),
+				"--all-functions" => arguments.entry_points.push("*".to_string()),
+				"--path-sensitive" => arguments.options.path_sensitive = true,
+				"--path-budget" => {
+					let budget = value(arg)?;
+					arguments.options.path_budget = budget.parse().map_err(|_| format!("Invalid path budget: {}", budget))?;
+				}
+				"--noreturn" => arguments.options.noreturn.push(value(arg)?),
+				"--escape" => arguments.options.escape = Escape::from_string(&value(arg)?)?,
+				"--summaries" => arguments.summaries.push(value(arg)?),
+				"--write-summaries" => arguments.write_summaries = Some(value(arg)?),
+				"-v" | "--verbose" => arguments.log_level = arguments.log_level.max(logger::Level::Verbose),
+				"--debug" => arguments.log_level = logger::Level::Debug,
+				"-h" | "--help" => return Err(USAGE.to_string()),
+				option if option.starts_with('-') => return Err(format!("Unknown option: {}", option)),
+				source => arguments.sources.push(source.to_string()),
+			}
+		}
+		if arguments.guards.is_empty() || arguments.sources.is_empty() {
+			return Err(USAGE.to_string());
+		}
+		if !arguments.entry_points.is_empty() {
+			arguments.options.entry_points = arguments.entry_points.clone();
+		}
+		return Ok(arguments);
+	}
+}
+
+fn main() -> ExitCode {
+	let args: Vec<String> = std::env::args().skip(1).collect();
+	let arguments = match Arguments::parse(&args) {
+		Ok(arguments) => arguments,
+		Err(message) => {
+			eprintln!("{}", message);
+			return ExitCode::from(2);
+		}
+	};
+
+	logger::set_level(arguments.log_level);
+
+	let mut tiny_guard = TinyGuard::new();
+	tiny_guard.set_options(arguments.options);
+	for guard_path in &arguments.guards {
+		if let Err(error) = tiny_guard.load_guards_from_path(guard_path) {
+			eprintln!("Failed to load {}: {}", guard_path, error);
+			return ExitCode::from(2);
+		}
+	}
+	debug!("GUARDS : {:#?}", tiny_guard.guards());
+	for summary_path in &arguments.summaries {
+		if let Err(error) = tiny_guard.load_summaries_from_path(summary_path) {
+			eprintln!("Failed to load {}: {}", summary_path, error);
+			return ExitCode::from(2);
+		}
+	}
+
+	let mut sources = Vec::new();
+	for source_path in &arguments.sources {
+		match std::fs::read_to_string(source_path) {
+			Ok(code) => sources.push((source_path.as_str(), code)),
+			Err(error) => {
+				eprintln!("Failed to read {}: {}", source_path, error);
+				return ExitCode::from(2);
+			}
+		};
+	}
+	// the files of a program call the functions of each other
+	if sources.len() > 1 || arguments.write_summaries.is_some() {
+		let sources = sources.iter().map(|(path, code)| (*path, code.as_str())).collect::<Vec<_>>();
+		let summaries = tiny_guard.link_sources(&sources);
+		debug!("LINKED SUMMARIES : {:#?}", summaries);
+		if let Some(path) = &arguments.write_summaries {
+			if let Err(error) = std::fs::write(path, summary::summaries_to_string(&summaries)) {
+				eprintln!("Failed to write {}: {}", path, error);
+				return ExitCode::from(2);
 			}
+			return ExitCode::SUCCESS;
 		}
 	}
 
-	let program = Program::from_vars(variables);
-	println!("PROGRAM: {:#?}", program);
+	let mut report = Report::new();
+	report.rules = tiny_guard.rules();
+	for (source_path, code) in &sources {
+		if logger::enabled(logger::Level::Debug) {
+			let tree = ast::parse_source(code);
+			ast::print_tree(tree.root_node(), code, 0);
+			for variable in &ast::parse_variables(&tree, code) {
+				ast::print_variables(variable, code);
+			}
+		}
+		report.extend(tiny_guard.report_source(source_path, code));
+	}
+
+	let rendered = output::render(arguments.format, &report);
+	match &arguments.output {
+		Some(path) => {
+			if let Err(error) = std::fs::write(path, rendered) {
+				eprintln!("Failed to write {}: {}", path, error);
+				return ExitCode::from(2);
+			}
+		}
+		None => print!("{}", rendered),
+	}
+	if report.has_errors() {
+		return ExitCode::FAILURE;
+	}
+	return ExitCode::SUCCESS;
 }
@@ -0,0 +1,467 @@
+mod html;
+mod sarif;
+mod xml;
+
+use crate::{
+	data_struct::{AbstractValue, Constant},
+	diagnostic::{Diagnostic, Location, Report, Severity},
+	json::Json,
+};
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum OutputFormat {
+	Human,
+	Gcc,
+	Json,
+	Sarif,
+	Html,
+	Junit,
+	Checkstyle,
+}
+
+impl OutputFormat {
+	pub fn from_string(format: &str) -> Result<OutputFormat, String> {
+		match format {
+			"human" => Ok(OutputFormat::Human),
+			"gcc" => Ok(OutputFormat::Gcc),
+			"json" => Ok(OutputFormat::Json),
+			"sarif" => Ok(OutputFormat::Sarif),
+			"html" => Ok(OutputFormat::Html),
+			"junit" => Ok(OutputFormat::Junit),
+			"checkstyle" => Ok(OutputFormat::Checkstyle),
+			_ => Err(format!("Unknown output format: {}", format)),
+		}
+	}
+}
+
+pub fn render(format: OutputFormat, report: &Report) -> String {
+	match format {
+		OutputFormat::Human => render_human(report),
+		OutputFormat::Gcc => render_gcc(report),
+		OutputFormat::Json => render_json(report),
+		OutputFormat::Sarif => sarif::render_sarif(report),
+		OutputFormat::Html => html::render_html(report),
+		OutputFormat::Junit => xml::render_junit(report),
+		OutputFormat::Checkstyle => xml::render_checkstyle(report),
+	}
+}
+
+/// Escapes text for the content and attribute values of both XML and HTML
+pub fn escape(s: &str) -> String {
+	let mut escaped = String::new();
+	for c in s.chars() {
+		match c {
+			'&' => escaped.push_str("&amp;"),
+			'<' => escaped.push_str("&lt;"),
+			'>' => escaped.push_str("&gt;"),
+			'"' => escaped.push_str("&quot;"),
+			'\'' => escaped.push_str("&apos;"),
+			'\n' => escaped.push_str("&#10;"),
+			'\t' | '\r' => escaped.push(c),
+			// not allowed in XML 1.0, even escaped
+			c if (c as u32) < 0x20 => {}
+			c => escaped.push(c),
+		}
+	}
+	return escaped;
+}
+
+/// Meta data as `name = value` pairs, e.g. `sorted = true, size = 3`
+pub fn meta_to_string(meta: &[(String, AbstractValue)]) -> String {
+	return meta
+		.iter()
+		.map(|(name, value)| format!("{} = {}", name, value))
+		.collect::<Vec<_>>()
+		.join(", ");
+}
+
+/*
+warning: Using search on an sorted vector is very inefficient [IntVector.IntVector_search.warn0]
+  --> samples/main.c:79:18
+   |
+79 |     size_t index2 = IntVector_search(&vec, 1);
+   |                     ^^^^^^^^^^^^^^^^^^^^^^^^^
+   = state: vec is IntVector { sorted = true }
+   = hint: Use IntVector_binary_search(&$self, ...) instead
+*/
+fn render_human(report: &Report) -> String {
+	let mut output = String::new();
+	for diagnostic in &report.diagnostics {
+		let location = &diagnostic.location;
+		let gutter = " ".repeat(location.line.to_string().len());
+		output.push_str(&format!(
+			"{}: {} [{}]\n",
+			diagnostic.severity.as_str(),
+			diagnostic.message,
+			diagnostic.rule_id
+		));
+		output.push_str(&format!("{}--> {}:{}:{}\n", gutter, location.file, location.line, location.column));
+		let line = report
+			.source(&location.file)
+			.and_then(|source| source.content.lines().nth(location.line - 1));
+		if let Some(line) = line {
+			let end_column = if location.end_line == location.line {
+				location.end_column
+			}
+			else {
+				line.len() + 1
+			};
+			let padding: String = line[..location.column - 1]
+				.chars()
+				.map(|c| match c {
+					'\t' => '\t',
+					_ => ' ',
+				})
+				.collect();
+			output.push_str(&format!("{} |\n", gutter));
+			output.push_str(&format!("{} | {}\n", location.line, line));
+			output.push_str(&format!(
+				"{} | {}{}\n",
+				gutter,
+				padding,
+				"^".repeat(end_column.saturating_sub(location.column).max(1))
+			));
+		}
+		if let Some(variable) = &diagnostic.variable {
+			output.push_str(&format!(
+				"{} = state: {} is {} {{ {} }}\n",
+				gutter,
+				variable,
+				diagnostic.data_struct,
+				meta_to_string(&diagnostic.meta)
+			));
+		}
+		if let Some(hint) = &diagnostic.hint {
+			output.push_str(&format!("{} = hint: {}\n", gutter, hint));
+		}
+		for related in &diagnostic.related {
+			let location = &related.location;
+			output.push_str(&format!(
+				"{} = note: {}:{}:{}: {}\n",
+				gutter, location.file, location.line, location.column, related.message
+			));
+		}
+		if !diagnostic.path.is_empty() {
+			output.push_str(&format!("{} = path: lines {}\n", gutter, path_to_string(&diagnostic.path)));
+		}
+		output.push('\n');
+	}
+	let errors = report.diagnostics.iter().filter(|d| d.severity == Severity::Error).count();
+	let warnings = report.diagnostics.iter().filter(|d| d.severity == Severity::Warning).count();
+	let notes = report.diagnostics.iter().filter(|d| d.severity == Severity::Note).count();
+	output.push_str(&format!("{} error(s), {} warning(s)", errors, warnings));
+	if notes > 0 {
+		output.push_str(&format!(", {} note(s)", notes));
+	}
+	output.push('\n');
+	return output;
+}
+
+/// One line per diagnostic, `file:line:col: severity: message [rule-id]`, the way GCC reports them
+fn render_gcc(report: &Report) -> String {
+	let mut output = String::new();
+	for diagnostic in &report.diagnostics {
+		let location = &diagnostic.location;
+		output.push_str(&format!(
+			"{}:{}:{}: {}: {} [{}]\n",
+			location.file,
+			location.line,
+			location.column,
+			diagnostic.severity.as_str(),
+			diagnostic.message,
+			diagnostic.rule_id
+		));
+		if let Some(hint) = &diagnostic.hint {
+			output.push_str(&format!(
+				"{}:{}:{}: note: {}\n",
+				location.file, location.line, location.column, hint
+			));
+		}
+		for related in &diagnostic.related {
+			let location = &related.location;
+			output.push_str(&format!(
+				"{}:{}:{}: note: {}\n",
+				location.file, location.line, location.column, related.message
+			));
+		}
+		if !diagnostic.path.is_empty() {
+			output.push_str(&format!(
+				"{}:{}:{}: note: path taken: lines {}\n",
+				location.file,
+				location.line,
+				location.column,
+				path_to_string(&diagnostic.path)
+			));
+		}
+	}
+	return output;
+}
+
+/// `3, 4, 7`
+fn path_to_string(path: &[usize]) -> String {
+	return path.iter().map(|line| line.to_string()).collect::<Vec<_>>().join(", ");
+}
+
+fn json_constant(constant: &Constant) -> Json {
+	match constant {
+		Constant::Bool(b) => Json::Bool(*b),
+		Constant::Number(n) => Json::Number(*n as i64),
+		Constant::String(s) => Json::str(s),
+	}
+}
+
+/// The value if it is known exactly, otherwise an array of the values it can be,
+/// an object with the `min` and `max` of its interval (null for no bound), or null if it can be anything
+fn json_value(value: &AbstractValue) -> Json {
+	if let Some(value) = value.exact() {
+		return json_constant(&value);
+	}
+	let bound = |bound: &Option<i32>| bound.map_or(Json::Null, |bound| Json::Number(bound as i64));
+	match value {
+		AbstractValue::OneOf(values) => return Json::Array(values.iter().map(json_constant).collect()),
+		AbstractValue::Interval { min, max } => return Json::object(vec![("min", bound(min)), ("max", bound(max))]),
+		AbstractValue::Unknown => return Json::Null,
+	}
+}
+
+pub fn json_meta(meta: &[(String, AbstractValue)]) -> Json {
+	return Json::Object(meta.iter().map(|(name, value)| (name.clone(), json_value(value))).collect());
+}
+
+fn json_location(location: &Location) -> Vec<(&'static str, Json)> {
+	return vec![
+		("file", Json::str(&location.file)),
+		("line", Json::Number(location.line as i64)),
+		("column", Json::Number(location.column as i64)),
+		("end_line", Json::Number(location.end_line as i64)),
+		("end_column", Json::Number(location.end_column as i64)),
+	];
+}
+
+fn json_diagnostic(diagnostic: &Diagnostic) -> Json {
+	let mut fields = vec![
+		("rule_id", Json::str(&diagnostic.rule_id)),
+		("severity", Json::str(diagnostic.severity.as_str())),
+		("message", Json::str(&diagnostic.message)),
+		("hint", Json::optional_str(&diagnostic.hint)),
+		("data_struct", Json::str(&diagnostic.data_struct)),
+		("variable", Json::optional_str(&diagnostic.variable)),
+	];
+	fields.extend(json_location(&diagnostic.location));
+	fields.push(("meta", json_meta(&diagnostic.meta)));
+	let related = diagnostic
+		.related
+		.iter()
+		.map(|related| {
+			let mut fields = json_location(&related.location);
+			fields.push(("message", Json::str(&related.message)));
+			return Json::object(fields);
+		})
+		.collect();
+	fields.push(("related", Json::Array(related)));
+	let path = diagnostic.path.iter().map(|&line| Json::Number(line as i64)).collect();
+	fields.push(("path", Json::Array(path)));
+	return Json::object(fields);
+}
+
+/// `{"version": 1, "diagnostics": [...]}`, the field names of a diagnostic are stable
+fn render_json(report: &Report) -> String {
+	let diagnostics = report.diagnostics.iter().map(json_diagnostic).collect();
+	let json = Json::object(vec![("version", Json::Number(1)), ("diagnostics", Json::Array(diagnostics))]);
+	return json.to_string_pretty();
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::diagnostic::{RelatedLocation, Rule, SourceFile, StateChange, Timeline};
+
+	fn sample_report() -> Report {
+		let mut report = Report::new();
+		report.sources.push(SourceFile {
+			path: "main.c".to_string(),
+			content: "int main() {\n\tIntVector_search(&vec, 1);\n}\n".to_string(),
+		});
+		report.rules.push(Rule {
+			id: "IntVector.IntVector_search.warn0".to_string(),
+			data_struct: "IntVector".to_string(),
+			call: "IntVector_search(&$self, ...)".to_string(),
+			severity: Severity::Warning,
+			message: "Search on a \"sorted\" vector".to_string(),
+			hint: Some("Use IntVector_binary_search".to_string()),
+			docs: Some("https://example.com/vector#search".to_string()),
+		});
+		let location = |line, column, end_column, start_byte, end_byte| Location {
+			file: "main.c".to_string(),
+			line,
+			column,
+			end_line: line,
+			end_column,
+			start_byte,
+			end_byte,
+		};
+		report.diagnostics.push(Diagnostic {
+			rule_id: "IntVector.IntVector_search.warn0".to_string(),
+			severity: Severity::Warning,
+			message: "Search on a \"sorted\" vector".to_string(),
+			hint: Some("Use IntVector_binary_search".to_string()),
+			data_struct: "IntVector".to_string(),
+			variable: Some("vec".to_string()),
+			meta: vec![("sorted".to_string(), AbstractValue::OneOf(vec![Constant::Bool(true)]))],
+			location: location(2, 2, 27, 14, 39),
+			related: vec![RelatedLocation {
+				location: location(1, 1, 11, 0, 10),
+				message: "`sorted` of `vec` set to true here".to_string(),
+			}],
+			path: vec![1, 2],
+		});
+		report.timelines.push(Timeline {
+			variable: "vec".to_string(),
+			data_struct: "IntVector".to_string(),
+			declaration: location(1, 1, 11, 0, 10),
+			initial: vec![("sorted".to_string(), AbstractValue::OneOf(vec![Constant::Bool(true)]))],
+			changes: vec![StateChange {
+				location: location(2, 2, 27, 14, 39),
+				call: "IntVector_search(&vec, 1)".to_string(),
+				meta: vec![("sorted".to_string(), AbstractValue::OneOf(vec![Constant::Bool(true)]))],
+			}],
+		});
+		return report;
+	}
+
+	#[test]
+	fn test_format_from_string() {
+		assert_eq!(OutputFormat::from_string("gcc"), Ok(OutputFormat::Gcc));
+		assert_eq!(OutputFormat::from_string("checkstyle"), Ok(OutputFormat::Checkstyle));
+		assert!(OutputFormat::from_string("xml").is_err());
+	}
+
+	#[test]
+	fn test_render_gcc() {
+		let output = render(OutputFormat::Gcc, &sample_report());
+		assert_eq!(
+			output,
+			"main.c:2:2: warning: Search on a \"sorted\" vector [IntVector.IntVector_search.warn0]\n\
+			 main.c:2:2: note: Use IntVector_binary_search\n\
+			 main.c:1:1: note: `sorted` of `vec` set to true here\n\
+			 main.c:2:2: note: path taken: lines 1, 2\n"
+		);
+	}
+
+	#[test]
+	fn test_render_json() {
+		let output = render(OutputFormat::Json, &sample_report());
+		assert!(output.contains("\"rule_id\": \"IntVector.IntVector_search.warn0\""));
+		assert!(output.contains("\"message\": \"Search on a \\\"sorted\\\" vector\""));
+		assert!(output.contains("\"variable\": \"vec\""));
+		assert!(output.contains("\"meta\": {\n\t\t\t\t\"sorted\": true\n\t\t\t}"));
+		assert!(output.contains("\"message\": \"`sorted` of `vec` set to true here\""));
+		assert!(output.contains("\"path\": [\n\t\t\t\t1,\n\t\t\t\t2\n\t\t\t]"));
+	}
+
+	#[test]
+	fn test_meta_values() {
+		let meta = vec![
+			(
+				"sorted".to_string(),
+				AbstractValue::OneOf(vec![Constant::Bool(true), Constant::Bool(false)]),
+			),
+			("size".to_string(), AbstractValue::Interval { min: Some(0), max: None }),
+			(
+				"capacity".to_string(),
+				AbstractValue::Interval {
+					min: Some(8),
+					max: Some(8),
+				},
+			),
+			("name".to_string(), AbstractValue::Unknown),
+		];
+		assert_eq!(
+			meta_to_string(&meta),
+			"sorted = true | false, size = [0, +inf], capacity = 8, name = unknown"
+		);
+		assert_eq!(
+			json_meta(&meta),
+			Json::Object(vec![
+				("sorted".to_string(), Json::Array(vec![Json::Bool(true), Json::Bool(false)])),
+				(
+					"size".to_string(),
+					Json::object(vec![("min", Json::Number(0)), ("max", Json::Null)])
+				),
+				("capacity".to_string(), Json::Number(8)),
+				("name".to_string(), Json::Null),
+			])
+		);
+	}
+
+	#[test]
+	fn test_render_human() {
+		let output = render(OutputFormat::Human, &sample_report());
+		assert!(output.contains(" --> main.c:2:2\n"));
+		assert!(output.contains("2 | \tIntVector_search(&vec, 1);\n"));
+		assert!(output.contains("  | \t^^^^^^^^^^^^^^^^^^^^^^^^^\n"));
+		assert!(output.contains(" = state: vec is IntVector { sorted = true }\n"));
+		assert!(output.contains(" = note: main.c:1:1: `sorted` of `vec` set to true here\n"));
+		assert!(output.contains(" = path: lines 1, 2\n"));
+	}
+
+	#[test]
+	fn test_render_sarif() {
+		let output = render(OutputFormat::Sarif, &sample_report());
+		assert!(output.contains("\"version\": \"2.1.0\""));
+		assert!(output.contains("\"name\": \"TinyGuard\""));
+		assert!(output.contains("\"helpUri\": \"https://example.com/vector#search\""));
+		assert!(output.contains("\"ruleIndex\": 0"));
+		assert!(output.contains("\"relatedLocations\": ["));
+		assert!(output.contains("\"threadFlows\": ["));
+		assert!(output.contains("\"uri\": \"main.c\""));
+		assert!(output.contains("\"startLine\": 2"));
+	}
+
+	#[test]
+	fn test_render_html() {
+		let output = render(OutputFormat::Html, &sample_report());
+		assert!(output.starts_with("<!DOCTYPE html>"));
+		assert!(!output.contains("<link") && !output.contains("<script"));
+		assert!(output.contains("Search on a &quot;sorted&quot; vector"));
+		assert!(output.contains("<tr id=\"file-0-line-2\" class=\"flagged\">"));
+		assert!(output.contains("<a href=\"#rule-IntVector.IntVector_search.warn0\">"));
+		assert!(output.contains("<tr id=\"rule-IntVector.IntVector_search.warn0\">"));
+		assert!(output.contains("<td><code>IntVector_search(&amp;vec, 1)</code></td><td>sorted = true</td>"));
+	}
+
+	#[test]
+	fn test_render_junit() {
+		let mut report = sample_report();
+		report.rules.push(Rule {
+			id: "IntVector.IntVector_binary_search.deny0".to_string(),
+			data_struct: "IntVector".to_string(),
+			call: "IntVector_binary_search(&$self, ...)".to_string(),
+			severity: Severity::Error,
+			message: "Not sorted".to_string(),
+			hint: None,
+			docs: None,
+		});
+		let output = render(OutputFormat::Junit, &report);
+		assert!(output.contains("<testsuites name=\"TinyGuard\" tests=\"2\" failures=\"1\" errors=\"0\">"));
+		assert!(output.contains("<testsuite name=\"IntVector\" tests=\"2\" failures=\"1\" errors=\"0\" skipped=\"0\">"));
+		assert!(
+			output.contains("<testcase classname=\"IntVector.IntVector_binary_search\" name=\"IntVector.IntVector_binary_search.deny0\"/>")
+		);
+		assert!(output.contains("<failure type=\"warning\" message=\"Search on a &quot;sorted&quot; vector (1 time(s))\">"));
+		assert!(output.contains("main.c:2:2: Search on a &quot;sorted&quot; vector&#10;state: vec is IntVector { sorted = true }"));
+	}
+
+	#[test]
+	fn test_render_checkstyle() {
+		let mut report = sample_report();
+		report.sources.push(SourceFile {
+			path: "clean.c".to_string(),
+			content: String::new(),
+		});
+		let output = render(OutputFormat::Checkstyle, &report);
+		assert!(output.contains("<file name=\"main.c\">\n\t\t<error line=\"2\" column=\"2\" severity=\"warning\""));
+		assert!(output.contains("source=\"IntVector.IntVector_search.warn0\"/>"));
+		assert!(output.contains("<file name=\"clean.c\"/>"));
+	}
+}
@@ -0,0 +1,229 @@
+use crate::{
+	diagnostic::{Diagnostic, Location, Report, Rule, Severity},
+	output::{escape, meta_to_string},
+};
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h1, h2, h3 { font-weight: normal; }
+table { border-collapse: collapse; margin-bottom: 1.5em; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: left; vertical-align: top; }
+th { background: #f0f0f0; }
+.error { color: #b00020; }
+.warning { color: #a65e00; }
+.note { color: #1a5fb4; }
+.source { border: none; font-family: monospace; white-space: pre; tab-size: 4; }
+.source td { border: none; padding: 0 0.6em; }
+.source .number { color: #888; text-align: right; user-select: none; }
+.source tr.flagged { background: #fff3cd; }
+.source tr.changed { background: #e8f4fd; }
+.annotation td { font-family: sans-serif; white-space: normal; padding-bottom: 0.3em; }
+code { background: #f5f5f5; padding: 0 0.2em; }
+";
+
+fn rule_anchor(rule_id: &str) -> String {
+	return format!("rule-{}", escape(rule_id));
+}
+
+fn line_anchor(report: &Report, file: &str, line: usize) -> String {
+	let index = report.sources.iter().position(|source| source.path == file).unwrap_or(0);
+	return format!("file-{}-line-{}", index, line);
+}
+
+fn location_link(report: &Report, location: &Location) -> String {
+	return format!(
+		"<a href=\"#{}\">{}:{}:{}</a>",
+		line_anchor(report, &location.file, location.line),
+		escape(&location.file),
+		location.line,
+		location.column
+	);
+}
+
+fn severity_class(severity: Severity) -> &'static str {
+	match severity {
+		Severity::Error => "error",
+		Severity::Warning => "warning",
+		Severity::Note => "note",
+	}
+}
+
+fn render_finding(report: &Report, diagnostic: &Diagnostic) -> String {
+	let variable = match &diagnostic.variable {
+		Some(variable) => format!(
+			"<code>{}</code> {{ {} }}",
+			escape(variable),
+			escape(&meta_to_string(&diagnostic.meta))
+		),
+		None => String::new(),
+	};
+	return format!(
+		"<tr><td class=\"{}\">{}</td><td>{}</td><td>{}</td><td>{}</td><td><a href=\"#{}\">{}</a></td></tr>\n",
+		severity_class(diagnostic.severity),
+		diagnostic.severity.as_str(),
+		location_link(report, &diagnostic.location),
+		escape(&diagnostic.message),
+		variable,
+		rule_anchor(&diagnostic.rule_id),
+		escape(&diagnostic.rule_id)
+	);
+}
+
+fn render_rule(report: &Report, rule: &Rule) -> String {
+	let fired = report.diagnostics.iter().filter(|d| d.rule_id == rule.id).count();
+	let docs = match &rule.docs {
+		Some(docs) => format!("<a href=\"{}\">documentation</a>", escape(docs)),
+		None => String::new(),
+	};
+	return format!(
+		"<tr id=\"{}\"><td><code>{}</code></td><td class=\"{}\">{}</td><td>{}</td><td><code>{}</code></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+		rule_anchor(&rule.id),
+		escape(&rule.id),
+		severity_class(rule.severity),
+		rule.severity.as_str(),
+		escape(&rule.data_struct),
+		escape(&rule.call),
+		escape(&rule.message),
+		escape(rule.hint.as_deref().unwrap_or_default()),
+		docs,
+		fired
+	);
+}
+
+/// A source file with its lines numbered, the lines with findings or state changes are highlighted
+/// and followed by what happened there
+fn render_source(report: &Report, index: usize) -> String {
+	let source = &report.sources[index];
+	let mut output = format!("<h3>{}</h3>\n<table class=\"source\">\n", escape(&source.path));
+	for (i, line) in source.content.lines().enumerate() {
+		let line_number = i + 1;
+		let findings: Vec<&Diagnostic> = report
+			.diagnostics
+			.iter()
+			.filter(|d| d.location.file == source.path && d.location.line == line_number)
+			.collect();
+		let changes: Vec<String> = report
+			.timelines
+			.iter()
+			.filter(|timeline| timeline.declaration.file == source.path)
+			.flat_map(|timeline| {
+				timeline
+					.changes
+					.iter()
+					.filter(|change| change.location.line == line_number)
+					.map(|change| {
+						format!(
+							"<code>{}</code> {{ {} }}",
+							escape(&timeline.variable),
+							escape(&meta_to_string(&change.meta))
+						)
+					})
+			})
+			.collect();
+		let class = if !findings.is_empty() {
+			" class=\"flagged\""
+		}
+		else if !changes.is_empty() {
+			" class=\"changed\""
+		}
+		else {
+			""
+		};
+		output.push_str(&format!(
+			"<tr id=\"{}\"{}><td class=\"number\">{}</td><td>{}</td></tr>\n",
+			line_anchor(report, &source.path, line_number),
+			class,
+			line_number,
+			escape(line)
+		));
+		for finding in findings {
+			output.push_str(&format!(
+				"<tr class=\"annotation\"><td></td><td><span class=\"{}\">{}</span>: {} (<a href=\"#{}\">{}</a>)</td></tr>\n",
+				severity_class(finding.severity),
+				finding.severity.as_str(),
+				escape(&finding.message),
+				rule_anchor(&finding.rule_id),
+				escape(&finding.rule_id)
+			));
+		}
+		for change in changes {
+			output.push_str(&format!("<tr class=\"annotation\"><td></td><td>now {}</td></tr>\n", change));
+		}
+	}
+	output.push_str("</table>\n");
+	return output;
+}
+
+fn render_timelines(report: &Report) -> String {
+	let mut output = String::new();
+	for timeline in &report.timelines {
+		output.push_str(&format!(
+			"<h3><code>{}</code> ({}), declared at {}</h3>\n",
+			escape(&timeline.variable),
+			escape(&timeline.data_struct),
+			location_link(report, &timeline.declaration)
+		));
+		output.push_str("<table>\n<tr><th>Step</th><th>Location</th><th>Call</th><th>Meta data after the call</th></tr>\n");
+		output.push_str(&format!(
+			"<tr><td>0</td><td>{}</td><td><em>declaration</em></td><td>{}</td></tr>\n",
+			location_link(report, &timeline.declaration),
+			escape(&meta_to_string(&timeline.initial))
+		));
+		for (i, change) in timeline.changes.iter().enumerate() {
+			output.push_str(&format!(
+				"<tr><td>{}</td><td>{}</td><td><code>{}</code></td><td>{}</td></tr>\n",
+				i + 1,
+				location_link(report, &change.location),
+				escape(&change.call),
+				escape(&meta_to_string(&change.meta))
+			));
+		}
+		output.push_str("</table>\n");
+	}
+	return output;
+}
+
+/// A self-contained HTML page, with no external stylesheet or script
+pub fn render_html(report: &Report) -> String {
+	let errors = report.diagnostics.iter().filter(|d| d.severity == Severity::Error).count();
+	let warnings = report.diagnostics.iter().filter(|d| d.severity == Severity::Warning).count();
+	let mut output = String::new();
+	output.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+	output.push_str("<title>TinyGuard report</title>\n");
+	output.push_str(&format!("<style>{}</style>\n</head>\n<body>\n", STYLE));
+	output.push_str("<h1>TinyGuard report</h1>\n");
+	output.push_str(&format!(
+		"<p>{} file(s) checked, {} error(s), {} warning(s).</p>\n",
+		report.sources.len(),
+		errors,
+		warnings
+	));
+
+	output.push_str("<h2>Findings</h2>\n");
+	if report.diagnostics.is_empty() {
+		output.push_str("<p>Nothing to report.</p>\n");
+	}
+	else {
+		output.push_str("<table>\n<tr><th>Severity</th><th>Location</th><th>Message</th><th>State</th><th>Rule</th></tr>\n");
+		for diagnostic in &report.diagnostics {
+			output.push_str(&render_finding(report, diagnostic));
+		}
+		output.push_str("</table>\n");
+	}
+
+	output.push_str("<h2>Sources</h2>\n");
+	for i in 0..report.sources.len() {
+		output.push_str(&render_source(report, i));
+	}
+
+	output.push_str("<h2>State timelines</h2>\n");
+	output.push_str(&render_timelines(report));
+
+	output.push_str("<h2>Guard rules</h2>\n");
+	output.push_str("<table>\n<tr><th>Rule</th><th>Severity</th><th>DataStruct</th><th>Call</th><th>Message</th><th>Hint</th><th>Docs</th><th>Fired</th></tr>\n");
+	for rule in &report.rules {
+		output.push_str(&render_rule(report, rule));
+	}
+	output.push_str("</table>\n</body>\n</html>\n");
+	return output;
+}
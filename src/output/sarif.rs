@@ -0,0 +1,177 @@
+use crate::{
+	diagnostic::{Diagnostic, Location, Report, Rule, Severity},
+	json::Json,
+	output::json_meta,
+};
+
+const SARIF_SCHEMA: &str = "https://docs.oasis-open.org/sarif/sarif/v2.1.0/errata01/os/schemas/sarif-schema-2.1.0.json";
+
+fn level(severity: Severity) -> &'static str {
+	match severity {
+		Severity::Error => "error",
+		Severity::Warning => "warning",
+		Severity::Note => "note",
+	}
+}
+
+/// A URI for a file path, as SARIF wants in `artifactLocation.uri`
+/// Relative paths stay relative references, absolute ones become `file://` URIs
+fn uri(path: &str) -> String {
+	let mut uri = String::new();
+	if path.starts_with('/') {
+		uri.push_str("file://");
+	}
+	for byte in path.replace('\\', "/").bytes() {
+		match byte {
+			b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => uri.push(byte as char),
+			_ => uri.push_str(&format!("%{:02X}", byte)),
+		}
+	}
+	return uri;
+}
+
+fn message(text: &str) -> Json {
+	return Json::object(vec![("text", Json::str(text))]);
+}
+
+fn artifact_location(report: &Report, file: &str) -> Json {
+	let mut artifact = vec![("uri", Json::String(uri(file)))];
+	if let Some(index) = report.sources.iter().position(|source| source.path == file) {
+		artifact.push(("index", Json::Number(index as i64)));
+	}
+	return Json::object(artifact);
+}
+
+fn physical_location(report: &Report, location: &Location) -> Json {
+	let region = Json::object(vec![
+		("startLine", Json::Number(location.line as i64)),
+		("startColumn", Json::Number(location.column as i64)),
+		("endLine", Json::Number(location.end_line as i64)),
+		("endColumn", Json::Number(location.end_column as i64)),
+	]);
+	return Json::object(vec![
+		("artifactLocation", artifact_location(report, &location.file)),
+		("region", region),
+	]);
+}
+
+/// A guard rule as a SARIF `reportingDescriptor`
+fn reporting_descriptor(rule: &Rule) -> Json {
+	let mut fields = vec![
+		("id", Json::str(&rule.id)),
+		("shortDescription", message(&rule.message)),
+		(
+			"fullDescription",
+			message(&format!("{} (on `{}` of {})", rule.message, rule.call, rule.data_struct)),
+		),
+	];
+	if let Some(hint) = &rule.hint {
+		fields.push(("help", message(hint)));
+	}
+	if let Some(docs) = &rule.docs {
+		fields.push(("helpUri", Json::str(docs)));
+	}
+	fields.push((
+		"defaultConfiguration",
+		Json::object(vec![("level", Json::str(level(rule.severity)))]),
+	));
+	fields.push((
+		"properties",
+		Json::object(vec![("dataStruct", Json::str(&rule.data_struct)), ("call", Json::str(&rule.call))]),
+	));
+	return Json::object(fields);
+}
+
+fn result(report: &Report, diagnostic: &Diagnostic) -> Json {
+	let mut fields = vec![("ruleId", Json::str(&diagnostic.rule_id))];
+	if let Some(index) = report.rules.iter().position(|rule| rule.id == diagnostic.rule_id) {
+		fields.push(("ruleIndex", Json::Number(index as i64)));
+	}
+	let text = match &diagnostic.hint {
+		Some(hint) => format!("{}\nHint: {}", diagnostic.message, hint),
+		None => diagnostic.message.clone(),
+	};
+	fields.push(("level", Json::str(level(diagnostic.severity))));
+	fields.push(("message", message(&text)));
+	fields.push((
+		"locations",
+		Json::Array(vec![Json::object(vec![(
+			"physicalLocation",
+			physical_location(report, &diagnostic.location),
+		)])]),
+	));
+	if !diagnostic.related.is_empty() {
+		let related = diagnostic
+			.related
+			.iter()
+			.enumerate()
+			.map(|(i, related)| {
+				Json::object(vec![
+					("id", Json::Number(i as i64)),
+					("physicalLocation", physical_location(report, &related.location)),
+					("message", message(&related.message)),
+				])
+			})
+			.collect();
+		fields.push(("relatedLocations", Json::Array(related)));
+	}
+	if !diagnostic.path.is_empty() {
+		// the lines taken, as the single thread of a code flow
+		let locations = diagnostic
+			.path
+			.iter()
+			.map(|&line| {
+				let region = Json::object(vec![("startLine", Json::Number(line as i64))]);
+				let physical_location = Json::object(vec![
+					("artifactLocation", artifact_location(report, &diagnostic.location.file)),
+					("region", region),
+				]);
+				return Json::object(vec![("location", Json::object(vec![("physicalLocation", physical_location)]))]);
+			})
+			.collect();
+		let thread_flow = Json::object(vec![("locations", Json::Array(locations))]);
+		fields.push((
+			"codeFlows",
+			Json::Array(vec![Json::object(vec![("threadFlows", Json::Array(vec![thread_flow]))])]),
+		));
+	}
+	fields.push((
+		"properties",
+		Json::object(vec![
+			("dataStruct", Json::str(&diagnostic.data_struct)),
+			("variable", Json::optional_str(&diagnostic.variable)),
+			("meta", json_meta(&diagnostic.meta)),
+		]),
+	));
+	return Json::object(fields);
+}
+
+/// A SARIF 2.1.0 log with a single run of TinyGuard
+pub fn render_sarif(report: &Report) -> String {
+	let driver = Json::object(vec![
+		("name", Json::str("TinyGuard")),
+		("informationUri", Json::str("https://github.com/Fur0rem/TinyGuard")),
+		("version", Json::str(env!("CARGO_PKG_VERSION"))),
+		("semanticVersion", Json::str(env!("CARGO_PKG_VERSION"))),
+		("rules", Json::Array(report.rules.iter().map(reporting_descriptor).collect())),
+	]);
+	let artifacts = report
+		.sources
+		.iter()
+		.map(|source| Json::object(vec![("location", Json::object(vec![("uri", Json::String(uri(&source.path)))]))]))
+		.collect();
+	let run = Json::object(vec![
+		("tool", Json::object(vec![("driver", driver)])),
+		("artifacts", Json::Array(artifacts)),
+		(
+			"results",
+			Json::Array(report.diagnostics.iter().map(|diagnostic| result(report, diagnostic)).collect()),
+		),
+	]);
+	let log = Json::object(vec![
+		("$schema", Json::str(SARIF_SCHEMA)),
+		("version", Json::str("2.1.0")),
+		("runs", Json::Array(vec![run])),
+	]);
+	return log.to_string_pretty();
+}
@@ -0,0 +1,113 @@
+use crate::{
+	diagnostic::{Diagnostic, Report, Severity},
+	output::escape,
+};
+
+fn failure_text(diagnostic: &Diagnostic) -> String {
+	let location = &diagnostic.location;
+	let mut text = format!("{}:{}:{}: {}", location.file, location.line, location.column, diagnostic.message);
+	if let Some(variable) = &diagnostic.variable {
+		text.push_str(&format!(
+			"\nstate: {} is {} {{ {} }}",
+			variable,
+			diagnostic.data_struct,
+			super::meta_to_string(&diagnostic.meta)
+		));
+	}
+	if let Some(hint) = &diagnostic.hint {
+		text.push_str(&format!("\nhint: {}", hint));
+	}
+	return text;
+}
+
+/// One test suite per DataStruct and one test case per rule, which fails if the rule fired
+pub fn render_junit(report: &Report) -> String {
+	let mut suites: Vec<&str> = Vec::new();
+	for rule in &report.rules {
+		if !suites.contains(&rule.data_struct.as_str()) {
+			suites.push(&rule.data_struct);
+		}
+	}
+	let failed = |rule_id: &str| report.diagnostics.iter().any(|d| d.rule_id == rule_id);
+	let total_failures = report.rules.iter().filter(|rule| failed(&rule.id)).count();
+
+	let mut output = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+	output.push_str(&format!(
+		"<testsuites name=\"TinyGuard\" tests=\"{}\" failures=\"{}\" errors=\"0\">\n",
+		report.rules.len(),
+		total_failures
+	));
+	for suite in suites {
+		let rules: Vec<_> = report.rules.iter().filter(|rule| rule.data_struct == suite).collect();
+		let failures = rules.iter().filter(|rule| failed(&rule.id)).count();
+		output.push_str(&format!(
+			"\t<testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"0\">\n",
+			escape(suite),
+			rules.len(),
+			failures
+		));
+		for rule in rules {
+			let classname = format!("{}.{}", rule.data_struct, rule.call.split('(').next().unwrap_or_default());
+			let diagnostics: Vec<&Diagnostic> = report.diagnostics.iter().filter(|d| d.rule_id == rule.id).collect();
+			if diagnostics.is_empty() {
+				output.push_str(&format!(
+					"\t\t<testcase classname=\"{}\" name=\"{}\"/>\n",
+					escape(&classname),
+					escape(&rule.id)
+				));
+				continue;
+			}
+			output.push_str(&format!(
+				"\t\t<testcase classname=\"{}\" name=\"{}\">\n",
+				escape(&classname),
+				escape(&rule.id)
+			));
+			let text = diagnostics.iter().map(|d| failure_text(d)).collect::<Vec<_>>().join("\n\n");
+			output.push_str(&format!(
+				"\t\t\t<failure type=\"{}\" message=\"{}\">{}</failure>\n",
+				rule.severity.as_str(),
+				escape(&format!("{} ({} time(s))", rule.message, diagnostics.len())),
+				escape(&text)
+			));
+			output.push_str("\t\t</testcase>\n");
+		}
+		output.push_str("\t</testsuite>\n");
+	}
+	output.push_str("</testsuites>\n");
+	return output;
+}
+
+/// One `<file>` per checked source, with an `<error>` per diagnostic found in it
+pub fn render_checkstyle(report: &Report) -> String {
+	let mut output = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"4.3\">\n");
+	for source in &report.sources {
+		let diagnostics: Vec<&Diagnostic> = report.diagnostics.iter().filter(|d| d.location.file == source.path).collect();
+		if diagnostics.is_empty() {
+			output.push_str(&format!("\t<file name=\"{}\"/>\n", escape(&source.path)));
+			continue;
+		}
+		output.push_str(&format!("\t<file name=\"{}\">\n", escape(&source.path)));
+		for diagnostic in diagnostics {
+			let severity = match diagnostic.severity {
+				Severity::Error => "error",
+				Severity::Warning => "warning",
+				Severity::Note => "info",
+			};
+			let message = match &diagnostic.hint {
+				Some(hint) => format!("{} (hint: {})", diagnostic.message, hint),
+				None => diagnostic.message.clone(),
+			};
+			output.push_str(&format!(
+				"\t\t<error line=\"{}\" column=\"{}\" severity=\"{}\" message=\"{}\" source=\"{}\"/>\n",
+				diagnostic.location.line,
+				diagnostic.location.column,
+				severity,
+				escape(&message),
+				escape(&diagnostic.rule_id)
+			));
+		}
+		output.push_str("\t</file>\n");
+	}
+	output.push_str("</checkstyle>\n");
+	return output;
+}
@@ -1,5 +1,7 @@
 use either::{self, Either};
 
+use crate::debug;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct FileLine {
 	pub line_number: usize,
@@ -17,6 +19,11 @@ impl FileLine {
 			line: self.line.trim().to_string(),
 		}
 	}
+
+	/// `message` prefixed by the number of the line, e.g. `line 3: Unknown handle kind`
+	pub fn located(&self, message: &str) -> String {
+		return format!("line {}: {}", self.line_number + 1, message);
+	}
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -38,7 +45,7 @@ impl Bracketed {
 
 // pub fn fill_in_between(b: &mut Vec<Bracketed>, content: &str)
 
-pub fn parse_bracketed(content: &str) -> Vec<Bracketed> {
+pub fn parse_bracketed(content: &str) -> Result<Vec<Bracketed>, String> {
 	let mut stack = Vec::new();
 	let mut bracketed_vec = Vec::new();
 	for (line_number, line) in content.lines().enumerate() {
@@ -51,11 +58,16 @@ pub fn parse_bracketed(content: &str) -> Vec<Bracketed> {
 			));
 		}
 		else if line.contains("}") {
-			let beginning_line = stack.pop().unwrap();
+			let Some(beginning_line) = stack.pop()
+			else {
+				return Err(FileLine::new(line_number, line.to_string()).located("`}` closes nothing"));
+			};
 			let ending_line = FileLine::new(line_number, line.to_string());
 			let mut content = Vec::new();
 			while let Some(bracketed) = bracketed_vec.pop() {
 				if bracketed.beginning_line.line_number == beginning_line.line_number {
+					// children were popped last to first
+					content.reverse();
 					bracketed_vec.push(Bracketed::new(beginning_line, ending_line, Either::Right(content)));
 					break;
 				}
@@ -65,7 +77,10 @@ pub fn parse_bracketed(content: &str) -> Vec<Bracketed> {
 			}
 		}
 	}
-	return bracketed_vec;
+	if let Some(unclosed) = stack.pop() {
+		return Err(unclosed.located("`{` is never closed"));
+	}
+	return Ok(bracketed_vec);
 }
 
 pub fn fill_blanks(bracketed: &Bracketed, text: &str) -> Bracketed {
@@ -80,7 +95,7 @@ pub fn fill_blanks(bracketed: &Bracketed, text: &str) -> Bracketed {
 				.map(|line| line.trim())
 			{
 				new_text.push_str(line);
-				new_text.push_str("\n");
+				new_text.push('\n');
 			}
 			Bracketed::new(
 				bracketed.beginning_line.clone().trimmed(),
@@ -90,9 +105,9 @@ pub fn fill_blanks(bracketed: &Bracketed, text: &str) -> Bracketed {
 		}
 		Either::Right(ref vec) => {
 			let mut new_vec = Vec::new();
-			println!("VEC: {:#?}", vec);
+			debug!("VEC: {:#?}", vec);
 			if vec.is_empty() {
-				println!("EMPTY");
+				debug!("EMPTY");
 				let lines = text.lines().map(|line| line.to_string()).collect::<Vec<_>>();
 				let mut new_text = String::new();
 				for line in lines
@@ -102,9 +117,9 @@ pub fn fill_blanks(bracketed: &Bracketed, text: &str) -> Bracketed {
 					.map(|line| line.trim())
 				{
 					new_text.push_str(line);
-					new_text.push_str("\n");
+					new_text.push('\n');
 				}
-				println!("NEW TEXT: {:?}", new_text);
+				debug!("NEW TEXT: {:?}", new_text);
 				return Bracketed::new(
 					bracketed.beginning_line.clone().trimmed(),
 					bracketed.ending_line.clone().trimmed(),
@@ -0,0 +1,835 @@
+//! Summaries of the functions of the checked code taking DataStructs, so that calling them changes the objects given to them.
+//! A summary is written as a call of the guard: `void prepare(IntVector* v) { IntVector_sort(v); }`
+//! is `prepare(&$self)` doing `sorted = true`.
+//! The summaries of a translation unit are saved in the same syntax as the guards,
+//! so that the calls made to its functions from other files can be checked
+
+use std::slice;
+
+use tree_sitter::Tree;
+
+use crate::{
+	ast::{self, CallSite, FunctionDefinition, ProgramVariable, SourceFacts},
+	cfg::{self, Cfg},
+	checker::{bind_self, call_rules, escape_policy, handled_variable, noreturn_functions, Options, PATH_ITERATIONS},
+	data_struct::{
+		expression_variables, substitute, AbstractEnvironment, AbstractValue, Assignement, Call, Constant, DataStruct, Escape, Operator,
+		Outcome, Truth, Warning,
+	},
+	verbose,
+};
+
+/// How many times the summaries are computed again, for the functions calling each other,
+/// before giving up on the ones still changing
+pub const MAX_ROUNDS: usize = 10;
+
+/// The most outcomes a summary has, past it the function is not summarised
+const MAX_OUTCOMES: usize = 16;
+
+/// What a function of the checked code does to the object one of its parameters holds
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+	pub data_struct: String,
+	/// The function as a call of the guard of the DataStruct, e.g. `prepare(&$self, n)`.
+	/// Its `Denies` and `Warn` are the rules the calls it makes on the object need when it is called,
+	/// and its outcomes what they do on each of its paths
+	pub call: Call,
+	/// Destroys the object on every path that returns
+	pub destructor: bool,
+}
+
+/// The guards with the summaries added to the calls and destructors of their DataStruct
+pub fn with_summaries(guards: &[DataStruct], summaries: &[Summary]) -> Vec<DataStruct> {
+	let mut guards = guards.to_vec();
+	for summary in summaries {
+		let Some(guard) = guards.iter_mut().find(|guard| guard.name == summary.data_struct)
+		else {
+			continue;
+		};
+		if summary.destructor {
+			guard.destructors.push(summary.call.signature().to_string());
+		}
+		guard.calls.push(summary.call.clone());
+	}
+	return guards;
+}
+
+/// The summaries as a summary file, a DataStruct for each of the DataStructs they are about
+pub fn summaries_to_string(summaries: &[Summary]) -> String {
+	let mut data_structs: Vec<&str> = Vec::new();
+	for summary in summaries {
+		if !data_structs.contains(&summary.data_struct.as_str()) {
+			data_structs.push(&summary.data_struct);
+		}
+	}
+	let mut summary_file = String::new();
+	for data_struct in data_structs {
+		let summaries = summaries.iter().filter(|summary| summary.data_struct == data_struct);
+		let destructors = summaries
+			.clone()
+			.filter(|summary| summary.destructor)
+			.map(|summary| format!("\t\t{}\n", summary.call.signature()))
+			.collect::<String>();
+		let calls = summaries
+			.flat_map(|summary| {
+				summary
+					.call
+					.to_guard()
+					.lines()
+					.map(|line| format!("\t\t{}\n", line))
+					.collect::<Vec<_>>()
+			})
+			.collect::<String>();
+		summary_file += &format!("DataStruct {} {{\n", data_struct);
+		if !destructors.is_empty() {
+			summary_file += &format!("\tDestructors {{\n{}\t}}\n", destructors);
+		}
+		summary_file += &format!("\tCalls {{\n{}\t}}\n}}\n", calls);
+	}
+	return summary_file;
+}
+
+/// The summaries of a summary file written by [`summaries_to_string`]
+pub fn parse_summaries(summary_file: &str) -> Result<Vec<Summary>, String> {
+	let mut summaries = Vec::new();
+	for data_struct in DataStruct::parse_summary_file(summary_file)? {
+		for mut call in data_struct.calls {
+			call.name = call.signature().to_string();
+			summaries.push(Summary {
+				data_struct: data_struct.name.clone(),
+				destructor: data_struct.destructors.iter().any(|destructor| destructor == call.signature()),
+				call,
+			});
+		}
+	}
+	return Ok(summaries);
+}
+
+/// The summaries of the functions `facts` does not define, the first one of each function only
+pub fn external(summaries: &[Summary], facts: &SourceFacts) -> Vec<Summary> {
+	let mut external: Vec<Summary> = Vec::new();
+	for summary in summaries {
+		let function = summary.call.function_name();
+		let defined = facts.functions.iter().any(|definition| definition.name == function);
+		let known = external
+			.iter()
+			.any(|other| other.data_struct == summary.data_struct && other.call.function_name() == function);
+		if !defined && !known {
+			external.push(summary.clone());
+		}
+	}
+	return external;
+}
+
+/// Whether a guard already tells what `function` does
+pub fn described(guards: &[DataStruct], function: &str) -> bool {
+	return guards.iter().any(|guard| {
+		let destructors = guard.destructor_calls();
+		return guard.is_constructor(function) || guard.calls.iter().chain(&destructors).any(|call| call.function_name() == function);
+	});
+}
+
+/// What changes the object of a parameter in a function
+enum Event<'a> {
+	Call(&'a CallSite),
+	/// (start_byte, end_byte) of the `return` statement
+	Return((usize, usize)),
+}
+
+impl Event<'_> {
+	fn range(&self) -> (usize, usize) {
+		match self {
+			Event::Call(call) => (call.start_byte, call.end_byte),
+			Event::Return(range) => *range,
+		}
+	}
+}
+
+/// A path of a function followed on its own, up to the start of `block`
+#[derive(Debug, Clone)]
+struct Path {
+	block: usize,
+	/// Run on the meta data of the object, in order
+	does: Vec<Assignement>,
+	destroyed: bool,
+	/// What the `return` ending the path returns, if it is a constant
+	returned: Option<i32>,
+	visits: Vec<usize>,
+}
+
+/// The assignments that change the meta data in the end, the ones set again before being read are dropped
+fn simplify(does: &[Assignement]) -> Vec<Assignement> {
+	let mut kept = Vec::new();
+	for (i, assignement) in does.iter().enumerate() {
+		let later = &does[i + 1..];
+		let overwritten = later.iter().position(|other| other.name == assignement.name).is_some_and(|j| {
+			return !later[..=j]
+				.iter()
+				.any(|other| expression_variables(&other.value).contains(&assignement.name));
+		});
+		if !overwritten {
+			kept.push(assignement.clone());
+		}
+	}
+	return kept;
+}
+
+/// A function of the checked code, with its control-flow graph and what happens in each of its blocks
+struct Function<'a> {
+	definition: &'a FunctionDefinition,
+	cfg: Cfg,
+	/// The events of each block with the index of their statement, in the order they run
+	events: Vec<Vec<(usize, Event<'a>)>>,
+}
+
+/// Follows the paths of a function to find what it does to the object of one of its parameters
+struct Summariser<'a> {
+	/// With the summaries found so far
+	guards: &'a [DataStruct],
+	/// The functions of the file taken as known before they are first summarised, so that they can call each other
+	assumed: &'a [&'a str],
+	guard: &'a DataStruct,
+	function: &'a Function<'a>,
+	/// As seen from the guard, see [`handled_variable`]
+	parameter: ProgramVariable,
+	source_code: &'a str,
+	tree: &'a Tree,
+	/// Whether the object is given to a function nothing is known of
+	escaped: bool,
+	/// The rules of those calls, with their kind, `deny` or `warn`, tested on the object as it is given to the function
+	requires: Vec<(&'static str, Warning)>,
+}
+
+impl Summariser<'_> {
+	/// Records that `warning` must not be broken by the object given to the function,
+	/// the test is the one of the rule once the assignments run before the call are undone
+	fn require(&mut self, kind: &'static str, warning: &Warning, call: &Call, does: &[Assignement]) {
+		let test = does
+			.iter()
+			.rev()
+			.fold(warning.test.clone(), |test, assignement| substitute(&test, assignement));
+		// never broken, whatever the object is
+		if expression_variables(&test).is_empty() && AbstractEnvironment::new().truth(&test) == Truth::Never {
+			return;
+		}
+		let mut message = match &warning.message {
+			Some(message) => message.clone(),
+			None => format!("`{}` breaks a rule of {}", call.signature(), self.guard.name),
+		};
+		// the functions the rule is broken in, once each for the ones calling themselves
+		let function = format!(" (in `{}`)", self.function.definition.name);
+		if !message.contains(&function) {
+			message.push_str(&function);
+		}
+		let warning = Warning {
+			test,
+			message: Some(message),
+			hint: warning.hint.clone(),
+			docs: warning.docs.clone(),
+		};
+		if !self.requires.contains(&(kind, warning.clone())) {
+			self.requires.push((kind, warning));
+		}
+	}
+
+	/// The paths going on after a call, one for each outcome it can have if it is made on the object
+	fn call(&mut self, path: Path, call_site: &CallSite) -> Vec<Path> {
+		// the function uses what it destroyed, which is for it to be checked on its own
+		if path.destroyed {
+			return vec![path];
+		}
+		let known = described(self.guards, &call_site.function) || self.assumed.contains(&call_site.function.as_str());
+		let given = call_site
+			.arguments
+			.iter()
+			.filter_map(|argument| argument.place.as_ref())
+			.any(|place| {
+				let depth = (self.parameter.pointer_depth + self.parameter.array_dimensions) as isize;
+				return place.variable == self.parameter.name && depth + place.indirection >= 0;
+			});
+		self.escaped |= !known && given;
+		let guard = self.guard;
+		let parameter = self.parameter.clone();
+		let bound = |call: &Call| {
+			return call.function_name() == call_site.function
+				&& bind_self(slice::from_ref(&parameter), call, call_site, &guard.name).is_some();
+		};
+		let mut paths = vec![path];
+		for call in guard.calls.iter().filter(|call| bound(call)) {
+			for (warnings, kind, _) in call_rules(call) {
+				for warning in warnings {
+					for path in &paths {
+						self.require(kind, warning, call, &path.does);
+					}
+				}
+			}
+			paths = paths
+				.into_iter()
+				.flat_map(|mut path| {
+					path.does.extend(call.does.iter().flatten().cloned());
+					return call.outcomes(None).into_iter().map(move |outcome| {
+						let mut path = path.clone();
+						path.does.extend(outcome.iter().flat_map(|outcome| outcome.does.iter().cloned()));
+						return path;
+					});
+				})
+				.collect();
+		}
+		if guard.destructor_calls().iter().any(bound) {
+			for path in &mut paths {
+				path.destroyed = true;
+			}
+		}
+		return paths;
+	}
+
+	/// What a `return` statement returns, if it is a constant
+	fn returned(&self, (start_byte, end_byte): (usize, usize)) -> Option<i32> {
+		let statement = self.tree.root_node().descendant_for_byte_range(start_byte, end_byte)?;
+		let value = statement.named_child(0)?;
+		if matches!(&self.source_code[value.start_byte()..value.end_byte()], "NULL" | "nullptr") {
+			return Some(0);
+		}
+		let expression = ast::expression(value, self.source_code)?;
+		if !expression_variables(&expression).is_empty() {
+			return None;
+		}
+		match AbstractEnvironment::new().evaluate(&expression) {
+			AbstractValue::Interval {
+				min: Some(min),
+				max: Some(max),
+			} if min == max => return Some(min),
+			AbstractValue::OneOf(values) => match values.as_slice() {
+				[Constant::Bool(b)] => return Some(*b as i32),
+				_ => return None,
+			},
+			_ => return None,
+		}
+	}
+
+	/// The paths from the entry reaching the end of the function, a path goes at most `PATH_ITERATIONS` times through a loop.
+	/// None if there are more than `budget`
+	fn returning_paths(&mut self, budget: usize) -> Option<Vec<Path>> {
+		let cfg = &self.function.cfg;
+		let loop_heads = cfg.loop_heads();
+		let mut returning = Vec::new();
+		let mut followed = 1;
+		let mut stack = vec![Path {
+			block: cfg.entry,
+			does: Vec::new(),
+			destroyed: false,
+			returned: None,
+			visits: vec![0; cfg.blocks.len()],
+		}];
+		while let Some(mut path) = stack.pop() {
+			let block = path.block;
+			path.visits[block] += 1;
+			if loop_heads[block] && path.visits[block] > PATH_ITERATIONS {
+				continue;
+			}
+			let mut paths = vec![path];
+			for (_, event) in &self.function.events[block] {
+				match event {
+					Event::Call(call_site) => paths = paths.into_iter().flat_map(|path| self.call(path, call_site)).collect(),
+					Event::Return(range) => {
+						let returned = self.returned(*range);
+						for path in &mut paths {
+							path.returned = returned;
+						}
+					}
+				}
+			}
+			if block == cfg.exit {
+				returning.extend(paths);
+				continue;
+			}
+			// the paths without successors call a function that never returns
+			let successors = &cfg.blocks[block].successors;
+			followed += paths.len() * successors.len();
+			if followed > budget {
+				return None;
+			}
+			for path in paths {
+				for &successor in successors.iter().rev() {
+					let mut path = path.clone();
+					path.block = successor;
+					stack.push(path);
+				}
+			}
+		}
+		return Some(returning);
+	}
+}
+
+/// The summaries of `function`, one for each parameter holding a DataStruct,
+/// except the ones given to functions nothing is known of when the escape policy is not to keep them as they are
+fn summarise_function(
+	guards: &[DataStruct], assumed: &[&str], function: &Function, file: &str, source_code: &str, tree: &Tree, options: &Options,
+) -> Vec<Summary> {
+	let mut summaries = Vec::new();
+	let definition = function.definition;
+	for (position, parameter) in definition.parameters.iter().enumerate() {
+		for guard in guards {
+			let Some(parameter) = handled_variable(parameter, guard)
+			else {
+				continue;
+			};
+			let depth = parameter.pointer_depth + parameter.array_dimensions;
+			let mut summariser = Summariser {
+				guards,
+				assumed,
+				guard,
+				function,
+				parameter,
+				source_code,
+				tree,
+				escaped: false,
+				requires: Vec::new(),
+			};
+			let Some(returning) = summariser.returning_paths(options.path_budget)
+			else {
+				verbose!(
+					"{}: `{}` has more than {} paths, it is not summarised",
+					file,
+					definition.name,
+					options.path_budget
+				);
+				continue;
+			};
+			// never returns
+			if returning.is_empty() {
+				continue;
+			}
+			if summariser.escaped && escape_policy(guard, options) != Escape::Keep {
+				verbose!(
+					"{}: `{}` gives `{}` to a function nothing is known of, it is not summarised",
+					file,
+					definition.name,
+					summariser.parameter.name
+				);
+				continue;
+			}
+
+			// the paths returning the same constant are told apart from the others, the last value found is `else`
+			let constants = returning.iter().all(|path| path.returned.is_some());
+			let last = returning.last().unwrap().returned;
+			let mut outcomes = Vec::new();
+			for path in &returning {
+				let outcome = Outcome {
+					returned: path
+						.returned
+						.filter(|&returned| constants && Some(returned) != last)
+						.map(|returned| (Operator::Equals, returned)),
+					does: simplify(&path.does),
+				};
+				if !outcomes.contains(&outcome) {
+					outcomes.push(outcome);
+				}
+			}
+			outcomes.sort_by_key(|outcome| outcome.returned.is_none());
+			if outcomes.len() > MAX_OUTCOMES {
+				verbose!(
+					"{}: `{}` does too many different things to `{}`, it is not summarised",
+					file,
+					definition.name,
+					summariser.parameter.name
+				);
+				continue;
+			}
+
+			let arguments = definition
+				.parameters
+				.iter()
+				.enumerate()
+				.map(|(i, other)| {
+					if i == position {
+						format!("{}$self", "&".repeat(depth))
+					}
+					// keeps the position of `$self`
+					else if other.name.is_empty() {
+						"_".to_string()
+					}
+					else {
+						other.name.clone()
+					}
+				})
+				.collect::<Vec<_>>();
+			let (does, returns) = match outcomes.as_slice() {
+				[Outcome { returned: None, does }] if does.is_empty() => (None, None),
+				[Outcome { returned: None, does }] => (Some(does.clone()), None),
+				_ => (None, Some(outcomes)),
+			};
+			let rules = |kind: &str| {
+				let warnings = summariser
+					.requires
+					.iter()
+					.filter(|(other, _)| *other == kind)
+					.map(|(_, warning)| warning.clone())
+					.collect::<Vec<_>>();
+				return Some(warnings).filter(|warnings| !warnings.is_empty());
+			};
+			summaries.push(Summary {
+				data_struct: guard.name.clone(),
+				call: Call {
+					name: format!("{}({})", definition.name, arguments.join(", ")),
+					does,
+					warn: rules("warn"),
+					deny: rules("deny"),
+					returns,
+				},
+				destructor: returning.iter().all(|path| path.destroyed),
+			});
+		}
+	}
+	return summaries;
+}
+
+/// Summarises the functions of `facts` taking DataStructs, except the entry points and the ones the guards already tell about,
+/// the functions of other files they call are the `external` summaries.
+/// Functions calling each other are summarised again until their summaries stop changing,
+/// the ones still changing after `MAX_ROUNDS` are not summarised
+pub fn summarise(
+	guards: &[DataStruct], external: &[Summary], options: &Options, file: &str, source_code: &str, tree: &Tree, facts: &SourceFacts,
+) -> Vec<Summary> {
+	let noreturn = noreturn_functions(options, facts);
+	let external = self::external(external, facts);
+	let functions = facts
+		.functions
+		.iter()
+		.filter(|function| {
+			let entry_point = options
+				.entry_points
+				.iter()
+				.any(|pattern| ast::matches_pattern(pattern, &function.name));
+			return !entry_point && !described(guards, &function.name);
+		})
+		.map(|definition| {
+			let body = tree
+				.root_node()
+				.descendant_for_byte_range(definition.body.0, definition.body.1)
+				.unwrap();
+			let cfg = cfg::build(body, source_code, &noreturn);
+			let mut events: Vec<Vec<(usize, Event)>> = cfg.blocks.iter().map(|_| Vec::new()).collect();
+			let calls = facts.calls.iter().map(Event::Call);
+			for event in calls.chain(facts.returns.iter().copied().map(Event::Return)) {
+				if let Some((block, statement)) = cfg.statement_at(event.range()) {
+					events[block].push((statement, event));
+				}
+			}
+			// in the order of the statements, the arguments of a call before the call
+			for events in &mut events {
+				events.sort_by_key(|(statement, event)| (*statement, event.range().1, event.range().0));
+			}
+			return Function { definition, cfg, events };
+		})
+		.collect::<Vec<_>>();
+
+	// the functions of the file are known from the start, until they turn out not to be summarised
+	let names = functions
+		.iter()
+		.map(|function| function.definition.name.as_str())
+		.collect::<Vec<_>>();
+	let round = |summaries: &[Vec<Summary>], assumed: &[&str]| {
+		let guards = with_summaries(guards, &[external.clone(), summaries.concat()].concat());
+		return functions
+			.iter()
+			.map(|function| summarise_function(&guards, assumed, function, file, source_code, tree, options))
+			.collect::<Vec<_>>();
+	};
+	let mut summaries = round(&vec![Vec::new(); functions.len()], &names);
+	for _ in 0..MAX_ROUNDS {
+		let next = round(&summaries, &[]);
+		if next == summaries {
+			return summaries.concat();
+		}
+		summaries = next;
+	}
+	let next = round(&summaries, &[]);
+	for (i, function) in functions.iter().enumerate() {
+		if next[i] != summaries[i] {
+			verbose!(
+				"{}: what `{}` does keeps changing with the calls it makes, it is not summarised",
+				file,
+				function.definition.name
+			);
+			summaries[i].clear();
+		}
+	}
+	return summaries.concat();
+}
+
+/// The summaries of the functions of a C source other files can call, the `static` ones are left out
+pub fn summarise_source(guards: &[DataStruct], external: &[Summary], options: &Options, file: &str, source_code: &str) -> Vec<Summary> {
+	let tree = ast::parse_source(source_code);
+	let facts = ast::extract(tree.root_node(), source_code);
+	let summaries = summarise(guards, external, options, file, source_code, &tree, &facts);
+	return summaries
+		.into_iter()
+		.filter(|summary| {
+			let function = summary.call.function_name();
+			return !facts
+				.functions
+				.iter()
+				.any(|definition| definition.name == function && definition.storage_class.as_deref() == Some("static"));
+		})
+		.collect();
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const GUARD: &str = "DataStruct IntVector {
+	Destructors {
+		IntVector_free(&$self)
+	}
+	Meta {
+		sorted: bool = true
+	}
+	Calls {
+		IntVector_push_back(&$self, ...) {
+			Does {
+				sorted = false
+			}
+		}
+		IntVector_sort(&$self) {
+			Does {
+				sorted = true
+			}
+		}
+		IntVector_binary_search(&$self, ...) {
+			Denies {
+				{
+					Test: sorted == false,
+					Message: \"vector is not sorted\"
+				}
+			}
+		}
+	}
+}";
+
+	fn summaries(source_code: &str) -> Vec<Summary> {
+		let guards = DataStruct::parse_guards(GUARD).unwrap();
+		let options = Options::default();
+		let tree = ast::parse_source(source_code);
+		let facts = ast::extract(tree.root_node(), source_code);
+		return summarise(&guards, &[], &options, "main.c", source_code, &tree, &facts);
+	}
+
+	#[test]
+	fn test_summaries() {
+		let summaries = summaries(
+			"void prepare(IntVector* v) {
+	IntVector_sort(v);
+}
+void add(IntVector* v, int n) {
+	if (n > 0) {
+		IntVector_push_back(v, n);
+	}
+}
+int search(int x, IntVector* v) {
+	IntVector_push_back(v, x);
+	IntVector_sort(v);
+	return IntVector_binary_search(v, x);
+}
+int lookup(IntVector* v, int x) {
+	if (x < 0) {
+		return -1;
+	}
+	IntVector_binary_search(v, x);
+	return 0;
+}
+void release(IntVector* v) {
+	IntVector_free(v);
+}
+void print(IntVector* v) {}
+void fail(IntVector* v) {
+	IntVector_sort(v);
+	exit(1);
+}
+int main() {}",
+		);
+		let signatures = summaries.iter().map(|summary| summary.call.signature()).collect::<Vec<_>>();
+		assert_eq!(
+			signatures,
+			vec![
+				"prepare(&$self)",
+				"add(&$self, n)",
+				"search(x, &$self)",
+				"lookup(&$self, x)",
+				"release(&$self)",
+				"print(&$self)"
+			]
+		);
+		let sorted = |value: &str| vec![Assignement::from_string(&format!("sorted = {}", value)).unwrap()];
+		assert_eq!(summaries[0].call.does, Some(sorted("true")));
+
+		// either sorts or not
+		let add = summaries[1].call.returns.as_ref().unwrap();
+		assert_eq!(add.iter().map(|outcome| outcome.returned).collect::<Vec<_>>(), vec![None, None]);
+		assert_eq!((add[0].does.clone(), add[1].does.clone()), (sorted("false"), Vec::new()));
+
+		// sorted again before being searched, the push back is forgotten
+		assert_eq!(
+			(summaries[2].call.does.clone(), &summaries[2].call.deny),
+			(Some(sorted("true")), &None)
+		);
+
+		// the search is only made when it returns 0
+		let lookup = &summaries[3].call;
+		let tests = lookup
+			.returns
+			.as_ref()
+			.unwrap()
+			.iter()
+			.map(|outcome| outcome.returned)
+			.collect::<Vec<_>>();
+		assert_eq!(tests, vec![Some((Operator::Equals, -1)), None]);
+		let deny = &lookup.deny.as_ref().unwrap()[0];
+		assert_eq!(deny.test, Warning::from_string("Test: sorted == false").unwrap().test);
+		assert_eq!(deny.message.as_deref(), Some("vector is not sorted (in `lookup`)"));
+
+		assert!(summaries[4].destructor);
+		assert!(!summaries[0].destructor);
+		// does nothing with the vector
+		assert_eq!(summaries[5].call, Call::from_signature("print(&$self)"));
+	}
+
+	#[test]
+	fn test_unnamed_parameters() {
+		let summaries = summaries("void unsort(int, IntVector* v) {\n\tIntVector_push_back(v, 1);\n}\nint main() {}");
+		assert_eq!(summaries[0].call.signature(), "unsort(_, &$self)");
+		// the vector is the second argument of the call
+		let guards = DataStruct::parse_guards(GUARD).unwrap();
+		let source_code = "int main() {
+	IntVector vec;
+	unsort(0, &vec);
+	IntVector_binary_search(&vec, 3);
+}";
+		let report = crate::checker::check_linked_source(&guards, &summaries, &Options::default(), "main.c", source_code);
+		let messages = report.diagnostics.iter().map(|d| d.message.as_str()).collect::<Vec<_>>();
+		assert_eq!(messages, vec!["vector is not sorted"]);
+	}
+
+	#[test]
+	fn test_entry_points_not_summarised() {
+		let source_code = "void test_sort(IntVector* v) {
+	IntVector_sort(v);
+}
+void prepare(IntVector* v) {
+	test_sort(v);
+}";
+		let guards = DataStruct::parse_guards(GUARD).unwrap();
+		// nothing is known of what the tests do, which calling them keeps as it is
+		let options = Options {
+			entry_points: vec!["test_*".to_string()],
+			escape: Escape::Keep,
+			..Options::default()
+		};
+		let tree = ast::parse_source(source_code);
+		let facts = ast::extract(tree.root_node(), source_code);
+		let summaries = summarise(&guards, &[], &options, "test.c", source_code, &tree, &facts);
+		let signatures = summaries.iter().map(|summary| summary.call.signature()).collect::<Vec<_>>();
+		assert_eq!(signatures, vec!["prepare(&$self)"]);
+		assert_eq!(summaries[0].call.does, None);
+	}
+
+	#[test]
+	fn test_escaped_summaries() {
+		let source_code = "void prepare(IntVector* v) {
+	IntVector_sort(v);
+	my_lib_helper(v);
+}
+void process(IntVector* v) {
+	prepare(v);
+}
+void report(IntVector* v, int n) {
+	log_size(n);
+	print(v);
+}
+void print(IntVector* v) {}
+int main() {}";
+		// what `my_lib_helper` does is unknown, and so is what the functions calling it do
+		let signatures = summaries(source_code)
+			.iter()
+			.map(|summary| summary.call.signature().to_string())
+			.collect::<Vec<_>>();
+		assert_eq!(signatures, vec!["report(&$self, n)", "print(&$self)"]);
+
+		let guards = DataStruct::parse_guards(&GUARD.replace("Meta {", "Handle {\n\t\tEscapes: keep\n\t}\n\tMeta {")).unwrap();
+		let tree = ast::parse_source(source_code);
+		let facts = ast::extract(tree.root_node(), source_code);
+		let summaries = summarise(&guards, &[], &Options::default(), "main.c", source_code, &tree, &facts);
+		assert_eq!(summaries.len(), 4);
+		assert_eq!(
+			summaries[1].call.does,
+			Some(vec![Assignement::from_string("sorted = true").unwrap()])
+		);
+	}
+
+	#[test]
+	fn test_recursive_summaries() {
+		let summaries = summaries(
+			"void fill(IntVector* v, int n) {
+	if (n == 0) {
+		check(v);
+		return;
+	}
+	IntVector_push_back(v, n);
+	IntVector_sort(v);
+	fill(v, n - 1);
+}
+void check(IntVector* v) {
+	IntVector_binary_search(v, 0);
+}
+int main() {}",
+		);
+		let signatures = summaries.iter().map(|summary| summary.call.signature()).collect::<Vec<_>>();
+		assert_eq!(signatures, vec!["fill(&$self, n)", "check(&$self)"]);
+		// every call of `fill` but the last one sorts the vector
+		let fill = &summaries[0].call;
+		let does = fill
+			.returns
+			.as_ref()
+			.unwrap()
+			.iter()
+			.map(|outcome| outcome.does.clone())
+			.collect::<Vec<_>>();
+		assert_eq!(does, vec![Vec::new(), vec![Assignement::from_string("sorted = true").unwrap()]]);
+		// the rule of `check` is only broken by the vectors `fill` is given, the ones it pushes back to are sorted first
+		let deny = fill.deny.as_ref().unwrap();
+		assert_eq!(deny.len(), 1);
+		assert_eq!(deny[0].message.as_deref(), Some("vector is not sorted (in `check`) (in `fill`)"));
+	}
+
+	#[test]
+	fn test_summary_file() {
+		let source_code = "int lookup(IntVector* v, int x) {
+	if (x < 0) {
+		return -1;
+	}
+	IntVector_binary_search(v, x);
+	return 0;
+}
+void release(IntVector* v) {
+	IntVector_free(v);
+}
+static void prepare(IntVector* v) {
+	IntVector_sort(v);
+}";
+		let guards = DataStruct::parse_guards(GUARD).unwrap();
+		let exported = summarise_source(&guards, &[], &Options::default(), "util.c", source_code);
+		let signatures = exported.iter().map(|summary| summary.call.signature()).collect::<Vec<_>>();
+		assert_eq!(signatures, vec!["lookup(&$self, x)", "release(&$self)"]);
+		let summary_file = summaries_to_string(&exported);
+		assert!(summary_file.starts_with("DataStruct IntVector {\n\tDestructors {\n\t\trelease(&$self)\n\t}\n"));
+		assert_eq!(parse_summaries(&summary_file).unwrap(), exported);
+
+		// the files defining a function use their own summary
+		let tree = ast::parse_source("void release(IntVector* v) {}\nint main() {}");
+		let facts = ast::extract(tree.root_node(), "void release(IntVector* v) {}\nint main() {}");
+		let external = external(&[exported.clone(), exported].concat(), &facts);
+		assert_eq!(external.len(), 1);
+		assert_eq!(external[0].call.signature(), "lookup(&$self, x)");
+	}
+}